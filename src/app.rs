@@ -7,11 +7,13 @@ use std::fmt;
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use winit::event::{ElementState, ModifiersState, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop, EventLoopProxy};
 
 use crate::commands::{Command, PuzzleCommand, PuzzleMouseCommand};
-use crate::logfile::LogFileFormat;
+use crate::logfile::{self, LogFileFormat};
 use crate::preferences::{Key, Keybind, PieceFilter, Preferences, Preset};
 use crate::puzzle::*;
 use crate::render::{GraphicsState, PuzzleRenderCache};
@@ -59,6 +61,10 @@ pub struct App {
     /// Grip that is more permanent.
     pub(crate) toggle_grip: Grip,
 
+    /// Cancellation flag for the scramble being generated on a worker thread,
+    /// if there is one.
+    pending_scramble: Option<Arc<AtomicBool>>,
+
     status_msg: String,
 }
 impl App {
@@ -85,6 +91,8 @@ impl App {
             transient_grips: HashMap::default(),
             toggle_grip: Grip::default(),
 
+            pending_scramble: None,
+
             status_msg: String::default(),
         };
 
@@ -95,6 +103,10 @@ impl App {
             this.prefs.log_file = Some(path);
         }
 
+        // Use the view preset last used with the default puzzle, unless a
+        // log file for another puzzle replaces it.
+        this.prefs.load_last_view_preset(this.puzzle.ty());
+
         // Load last open file.
         #[cfg(target_arch = "wasm32")]
         this.try_load_from_local_storage();
@@ -156,7 +168,7 @@ impl App {
                     unsupported_on_web! {
                         self;
                         if self.confirm_discard_changes("open another file") {
-                            if let Some(path) = file_dialog().pick_file() {
+                            if let Some(path) = log_file_dialog().pick_file() {
                                 self.try_load_puzzle(path);
                             }
                         }
@@ -202,17 +214,50 @@ impl App {
                     if self.confirm_discard_changes("scramble") {
                         self.puzzle.scramble_n(n)?;
                         self.set_status_ok(format!(
-                            "Scrambled with {} random {}",
+                            "Scrambled with {} random {} ({:.0}% of pieces displaced)",
                             n,
-                            if n == 1 { "move" } else { "moves" }
+                            if n == 1 { "move" } else { "moves" },
+                            self.puzzle.scramble_quality() * 100.0,
                         ));
                         self.timer.on_scramble();
                     }
                 }
                 Command::ScrambleFull => {
                     if self.confirm_discard_changes("scramble") {
-                        self.puzzle.scramble_full()?;
-                        self.set_status_ok("Scrambled fully");
+                        let seed = self.puzzle.scramble_full()?;
+                        self.set_status_ok(format!(
+                            "Scrambled fully (seed {seed}, {:.0}% of pieces displaced)",
+                            self.puzzle.scramble_quality() * 100.0,
+                        ));
+                        self.timer.on_scramble();
+                    }
+                }
+                Command::ScrambleRandomState => {
+                    if self.confirm_discard_changes("scramble") {
+                        self.start_random_state_scramble();
+                    }
+                }
+                Command::ScramblePieceType(piece_type) => {
+                    if self.confirm_discard_changes("scramble") {
+                        self.puzzle.scramble_piece_type(&piece_type)?;
+                        self.set_status_ok(format!(
+                            "Scrambled only {piece_type}s ({:.0}% of pieces displaced)",
+                            self.puzzle.scramble_quality() * 100.0,
+                        ));
+                        self.timer.on_scramble();
+                    }
+                }
+                Command::ApplyPattern(name) => {
+                    if self.confirm_discard_changes("apply pattern") {
+                        let pattern = self
+                            .puzzle
+                            .named_patterns()
+                            .iter()
+                            .find(|p| p.name == name)
+                            .ok_or("unknown pattern")?;
+                        self.puzzle.apply_pattern(pattern)?;
+                        self.set_status_ok(format!("Applied {} pattern", pattern.name));
+                        self.warn_if_unsolvable();
                         self.timer.on_scramble();
                     }
                 }
@@ -221,9 +266,15 @@ impl App {
                     if self.confirm_discard_changes("reset puzzle") {
                         self.puzzle = PuzzleController::new(puzzle_type);
                         self.set_status_ok(format!("Loaded {}", puzzle_type));
+                        self.on_puzzle_opened();
                     }
                 }
 
+                Command::ImportSettings => unsupported_on_web! { self; self.try_import_settings() },
+                Command::ExportSettings => unsupported_on_web! { self; self.try_export_settings() },
+                Command::ImportColors => unsupported_on_web! { self; self.try_import_colors() },
+                Command::ExportColors => unsupported_on_web! { self; self.try_export_colors() },
+
                 Command::ToggleBlindfold => {
                     self.prefs.colors.blindfold ^= true;
                     if self.prefs.colors.blindfold {
@@ -279,6 +330,27 @@ impl App {
                 }
             }
 
+            AppEvent::ScrambleProgress { cancel, nodes } => {
+                if self.is_pending_scramble(&cancel) {
+                    self.set_status_ok(format!(
+                        "Generating random-state scramble ({nodes} states searched)",
+                    ));
+                }
+            }
+            AppEvent::ScrambleGenerated { cancel, result } => {
+                if self.is_pending_scramble(&cancel) {
+                    self.pending_scramble = None;
+                    let scramble = result?;
+                    self.puzzle.apply_scramble(&scramble, ScrambleState::Full)?;
+                    self.set_status_ok(format!(
+                        "Scrambled to a random state in {} twists ({:.0}% of pieces displaced)",
+                        scramble.len(),
+                        self.puzzle.scramble_quality() * 100.0,
+                    ));
+                    self.timer.on_scramble();
+                }
+            }
+
             AppEvent::StatusError(msg) => return Err(msg),
         }
 
@@ -577,6 +649,9 @@ impl App {
                         let old = std::mem::replace(&mut presets.current, preset.value.clone());
                         self.puzzle.animate_from_view_settings(old);
                         presets.active_preset = Some(preset.clone());
+                        let puzzle_type = self.puzzle.ty();
+                        self.prefs
+                            .set_last_view_preset(puzzle_type, view_preset_name.clone());
                         self.prefs.needs_save = true;
                     }
                 }
@@ -769,13 +844,70 @@ impl App {
         }
     }
 
-    fn confirm_load_puzzle(&self, warnings: &[String]) -> bool {
+    /// Starts generating a random-state scramble, which is applied once it's
+    /// ready unless it is cancelled first.
+    fn start_random_state_scramble(&mut self) {
+        let ty = self.puzzle.ty();
+        let seed = rand::random();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel_pending_scramble();
+        self.pending_scramble = Some(Arc::clone(&cancel));
+        self.set_status_ok("Generating random-state scramble");
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let events = self.events.clone();
+            std::thread::spawn(move || {
+                let result = PuzzleController::generate_random_state_scramble(
+                    ty,
+                    seed,
+                    &cancel,
+                    |nodes, _| {
+                        let cancel = Arc::clone(&cancel);
+                        let _ = events.send_event(AppEvent::ScrambleProgress { cancel, nodes });
+                    },
+                );
+                let _ = events.send_event(AppEvent::ScrambleGenerated { cancel, result });
+            });
+        }
+        // There are no threads on web, so generate the scramble right away.
+        #[cfg(target_arch = "wasm32")]
+        {
+            let result =
+                PuzzleController::generate_random_state_scramble(ty, seed, &cancel, |_, _| ());
+            self.event(AppEvent::ScrambleGenerated { cancel, result });
+        }
+    }
+    fn is_pending_scramble(&self, cancel: &Arc<AtomicBool>) -> bool {
+        self.pending_scramble
+            .as_ref()
+            .map_or(false, |pending| Arc::ptr_eq(pending, cancel))
+    }
+    fn cancel_pending_scramble(&mut self) {
+        if let Some(cancel) = self.pending_scramble.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn confirm_load_puzzle(&self, warnings: &[logfile::Warning]) -> bool {
+        let is_error = warnings
+            .iter()
+            .any(|w| w.severity == logfile::Severity::Error);
         warnings.is_empty()
             || rfd::MessageDialog::new()
-                .set_title("Errors loading file")
+                .set_title(if is_error {
+                    "Errors loading file"
+                } else {
+                    "Warnings loading file"
+                })
                 .set_description(&format!(
-                    "The following errors were encountered \
+                    "The following {} encountered \
                      while loading this file. Load anyway?\n\n{}",
+                    if is_error {
+                        "errors were"
+                    } else {
+                        "warnings were"
+                    },
                     warnings.iter().join("\n"),
                 ))
                 .set_buttons(rfd::MessageButtons::YesNo)
@@ -798,6 +930,7 @@ impl App {
                 .set_buttons(rfd::MessageButtons::YesNo)
                 .show();
         if confirm {
+            self.cancel_pending_scramble();
             self.prefs.log_file = None;
             self.prefs.needs_save = true;
         }
@@ -808,12 +941,14 @@ impl App {
         match crate::logfile::deserialize(log_file_contents) {
             Ok((puzzle, warnings)) => {
                 if self.confirm_load_puzzle(&warnings) {
+                    self.cancel_pending_scramble();
                     self.puzzle = puzzle;
+                    self.on_puzzle_opened();
 
                     self.set_status_ok("Loaded puzzle log file from clipboard");
+                    self.warn_if_unsolvable();
 
                     self.prefs.log_file = None;
-                    self.prefs.needs_save = true;
                 }
             }
             Err(e) => {
@@ -841,11 +976,12 @@ impl App {
             Ok((puzzle, warnings)) => {
                 if self.confirm_load_puzzle(&warnings) {
                     self.puzzle = puzzle;
+                    self.on_puzzle_opened();
 
                     self.set_status_ok(format!("Loaded log file from {}", path.display()));
+                    self.warn_if_unsolvable();
 
                     self.prefs.log_file = Some(path);
-                    self.prefs.needs_save = true;
                 }
             }
             Err(e) => show_error_dialog(
@@ -869,11 +1005,72 @@ impl App {
     }
     #[cfg(not(target_arch = "wasm32"))]
     fn try_save_puzzle_as(&mut self) {
-        if let Some(path) = file_dialog().save_file() {
+        if let Some(path) = log_file_dialog().save_file() {
             self.try_save_puzzle(&path)
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_import_settings(&mut self) {
+        if let Some(path) = settings_file_dialog().pick_file() {
+            let result = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|s| self.prefs.import_bundle(&s));
+            match result {
+                Ok(()) => {
+                    self.request_redraw_puzzle();
+                    self.set_status_ok(format!("Imported settings from {}", path.display()));
+                }
+                Err(e) => show_error_dialog("Unable to import settings", e),
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_export_settings(&mut self) {
+        if let Some(path) = settings_file_dialog().save_file() {
+            let result = self
+                .prefs
+                .export_bundle()
+                .and_then(|s| Ok(std::fs::write(&path, s)?));
+            match result {
+                Ok(()) => self.set_status_ok(format!("Exported settings to {}", path.display())),
+                Err(e) => show_error_dialog("Unable to export settings", e),
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_import_colors(&mut self) {
+        let puzzle_type = self.puzzle.ty();
+        if let Some(path) = color_scheme_file_dialog().pick_file() {
+            let result = std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|s| self.prefs.colors.import_face_colors(puzzle_type, &s));
+            match result {
+                Ok(()) => {
+                    self.prefs.needs_save = true;
+                    self.request_redraw_puzzle();
+                    self.set_status_ok(format!("Imported colors from {}", path.display()));
+                }
+                Err(e) => show_error_dialog("Unable to import colors", e),
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    fn try_export_colors(&mut self) {
+        let puzzle_type = self.puzzle.ty();
+        if let Some(path) = color_scheme_file_dialog().save_file() {
+            let result = self
+                .prefs
+                .colors
+                .export_face_colors(puzzle_type)
+                .and_then(|s| Ok(std::fs::write(&path, s)?));
+            match result {
+                Ok(()) => self.set_status_ok(format!("Exported colors to {}", path.display())),
+                Err(e) => show_error_dialog("Unable to export colors", e),
+            }
+        }
+    }
+
     #[cfg(target_arch = "wasm32")]
     const LOCAL_STORAGE_KEY: &str = "hyperspeedcube_puzzle_log";
     #[cfg(target_arch = "wasm32")]
@@ -906,6 +1103,26 @@ impl App {
         if self.confirm_load_puzzle(&warnings) {
             self.puzzle = p;
             self.puzzle.mark_saved_in_local_storage();
+            self.on_puzzle_opened();
+            self.warn_if_unsolvable();
+        }
+    }
+
+    /// Updates preferences after opening a puzzle and switches to the view
+    /// preset last used with it.
+    fn on_puzzle_opened(&mut self) {
+        if let Some(old) = self.prefs.open_puzzle(self.puzzle.ty()) {
+            self.puzzle.animate_from_view_settings(old);
+        }
+    }
+
+    /// Shows an error in the status bar if the puzzle is in a state that can't
+    /// be solved.
+    fn warn_if_unsolvable(&mut self) {
+        if self.puzzle.has_unsolvable_parity() {
+            self.set_status_err(
+                "This puzzle state has odd permutation parity, so it can't be solved",
+            );
         }
     }
 
@@ -955,6 +1172,19 @@ pub(crate) enum AppEvent {
     Drag(egui::Vec2),
     DragReleased,
 
+    /// Progress update from a scramble being generated on a worker thread.
+    ScrambleProgress {
+        cancel: Arc<AtomicBool>,
+        /// Number of puzzle states searched so far.
+        nodes: usize,
+    },
+    /// Scramble generated on a worker thread, identified by the cancellation
+    /// flag that was given to the thread.
+    ScrambleGenerated {
+        cancel: Arc<AtomicBool>,
+        result: Result<Vec<Twist>, &'static str>,
+    },
+
     StatusError(String),
 
     #[cfg(target_arch = "wasm32")]
@@ -979,12 +1209,24 @@ pub(crate) struct AppEventResponse {
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn file_dialog() -> rfd::FileDialog {
+fn file_dialog(filter_name: &str, extensions: &[&str]) -> rfd::FileDialog {
     rfd::FileDialog::new()
-        .add_filter("Hyperspeedcube Log Files", &["hsc", "log"])
+        .add_filter(filter_name, extensions)
         .add_filter("All files", &["*"])
 }
 #[cfg(not(target_arch = "wasm32"))]
+fn log_file_dialog() -> rfd::FileDialog {
+    file_dialog("Hyperspeedcube Log Files", &["hsc", "log"])
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn settings_file_dialog() -> rfd::FileDialog {
+    file_dialog("Settings", &["yaml", "yml"])
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn color_scheme_file_dialog() -> rfd::FileDialog {
+    file_dialog("Color schemes", &["yaml", "yml"])
+}
+#[cfg(not(target_arch = "wasm32"))]
 fn show_error_dialog(title: &str, e: impl fmt::Display) {
     rfd::MessageDialog::new()
         .set_title(title)
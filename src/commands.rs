@@ -34,10 +34,19 @@ pub enum Command {
     // Scramble menu
     ScrambleN(usize),
     ScrambleFull,
+    ScrambleRandomState,
+    ScramblePieceType(String),
+    ApplyPattern(String),
 
     // Puzzle menu
     NewPuzzle(PuzzleTypeEnum),
 
+    // Settings (local)
+    ImportSettings,
+    ExportSettings,
+    ImportColors,
+    ExportColors,
+
     ToggleBlindfold,
 
     #[default]
@@ -62,9 +71,17 @@ impl Command {
 
             Command::ScrambleN(n) => format!("🔀 {n}"),
             Command::ScrambleFull => "🔀".to_owned(),
+            Command::ScrambleRandomState => "🔀 RS".to_owned(),
+            Command::ScramblePieceType(piece_type) => format!("🔀 {piece_type}s"),
+            Command::ApplyPattern(name) => format!("🔀 {name}"),
 
             Command::NewPuzzle(ty) => format!("New {}", ty.name()),
 
+            Command::ImportSettings => "Import settings".to_owned(),
+            Command::ExportSettings => "Export settings".to_owned(),
+            Command::ImportColors => "Import colors".to_owned(),
+            Command::ExportColors => "Export colors".to_owned(),
+
             Command::ToggleBlindfold => "BLD".to_owned(),
 
             Command::None => String::new(),
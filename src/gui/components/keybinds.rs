@@ -255,8 +255,16 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
 
                     "Scramble partially" => Cmd::ScrambleN(PARTIAL_SCRAMBLE_MOVE_COUNT_MIN),
                     "Scramble fully" => Cmd::ScrambleFull,
+                    "Scramble random state" => Cmd::ScrambleRandomState,
+                    "Scramble piece type" => Cmd::ScramblePieceType("corner".to_owned()),
+                    "Apply pattern" => Cmd::ApplyPattern("Checkerboard".to_owned()),
                     "Toggle blindfold" => Cmd::ToggleBlindfold,
                     "New puzzle" => Cmd::NewPuzzle(PuzzleTypeEnum::default()),
+
+                    "Import settings..." => Cmd::ImportSettings,
+                    "Export settings..." => Cmd::ExportSettings,
+                    "Import colors..." => Cmd::ImportColors,
+                    "Export colors..." => Cmd::ExportColors,
                 }
             );
             changed |= r.changed();
@@ -269,6 +277,11 @@ impl egui::Widget for CommandSelectWidget<'_, GlobalKeybindsAccessor> {
                     changed |= r.changed();
                 }
 
+                Cmd::ScramblePieceType(name) | Cmd::ApplyPattern(name) => {
+                    let r = ui.add(egui::TextEdit::singleline(name).desired_width(100.0));
+                    changed |= r.changed();
+                }
+
                 Cmd::NewPuzzle(puzzle_type) => {
                     if let Some(Some(ty)) =
                         ui.menu_button(puzzle_type.name(), puzzle_type_menu).inner
@@ -7,6 +7,7 @@ mod presets;
 mod puzzle_list;
 mod reorder;
 mod reset;
+mod twist_entry;
 mod yaml_editor;
 
 pub use combo_boxes::*;
@@ -17,6 +18,7 @@ pub use presets::*;
 pub use puzzle_list::*;
 pub use reorder::*;
 pub use reset::*;
+pub use twist_entry::*;
 pub use yaml_editor::*;
 
 pub const BIG_ICON_BUTTON_SIZE: egui::Vec2 = egui::vec2(22.0, 22.0);
@@ -1,10 +1,12 @@
 use egui::NumExt;
 
 use crate::app::App;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::commands::Command;
 use crate::gui::components::{with_reset_button, PresetsUi, WidgetWithReset};
 use crate::gui::ext::*;
 use crate::gui::util::Access;
-use crate::preferences::{OpacityPreferences, DEFAULT_PREFS};
+use crate::preferences::{OpacityPreferences, DEFAULT_PREFS, MAX_RENDER_SCALE, MIN_RENDER_SCALE};
 use crate::puzzle::{traits::*, Face, ProjectionType};
 use crate::serde_impl::hex_color;
 
@@ -142,6 +144,17 @@ pub fn build_colors_section(ui: &mut egui::Ui, app: &mut App) {
     for (i, &face) in puzzle_type.faces().iter().enumerate() {
         prefs_ui.color(face.name, access!([(puzzle_type, Face(i as _))]));
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut file_command = None;
+    #[cfg(not(target_arch = "wasm32"))]
+    prefs_ui.ui.horizontal(|ui| {
+        if ui.button("Import colors…").clicked() {
+            file_command = Some(Command::ImportColors);
+        }
+        if ui.button("Export colors…").clicked() {
+            file_command = Some(Command::ExportColors);
+        }
+    });
 
     prefs_ui.ui.separator();
 
@@ -154,6 +167,10 @@ pub fn build_colors_section(ui: &mut egui::Ui, app: &mut App) {
     if changed {
         app.request_redraw_puzzle();
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(command) = file_command {
+        app.event(command);
+    }
 }
 pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
     let prefs = &mut app.prefs;
@@ -188,6 +205,19 @@ pub fn build_graphics_section(ui: &mut egui::Ui, app: &mut App) {
             );
     });
 
+    prefs_ui
+        .num("Render scale", access!(.render_scale), |dv| {
+            dv.fixed_decimals(2)
+                .clamp_range(MIN_RENDER_SCALE..=MAX_RENDER_SCALE)
+                .speed(0.01)
+        })
+        .on_hover_explanation(
+            "",
+            "Resolution of the puzzle relative to its size \
+             on screen. Values above 1 make edges smoother, \
+             but may worsen performance.",
+        );
+
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
@@ -343,6 +373,7 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
     let presets = prefs.view_presets(&app.puzzle);
 
     let mut changed = false;
+    let mut loaded_preset_name = None;
 
     ui.collapsing("Presets", |ui| {
         let mut presets_ui = PresetsUi {
@@ -367,6 +398,7 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
                     let old = std::mem::replace(&mut presets.current, preset.value.clone());
                     app.puzzle.animate_from_view_settings(old);
                     presets.active_preset = Some(preset.clone());
+                    loaded_preset_name = Some(preset.preset_name.clone());
                     changed = true;
                 }
                 if presets.active_preset.as_ref() == Some(preset) {
@@ -447,6 +479,10 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         prefs_ui.num("Sticker spacing", access!(.sticker_spacing), |dv| {
             dv.fixed_decimals(2).clamp_range(0.0..=0.9_f32).speed(0.005)
         });
+
+        prefs_ui.num("Piece explode", access!(.piece_explode), |dv| {
+            dv.fixed_decimals(2).clamp_range(0.0..=2.0_f32).speed(0.005)
+        });
     });
 
     prefs_ui.collapsing("Lighting", |mut prefs_ui| {
@@ -460,6 +496,9 @@ pub fn build_view_section(ui: &mut egui::Ui, app: &mut App) {
         prefs_ui.percent("Ambient", access!(.light_ambient));
     });
 
+    if let Some(preset_name) = loaded_preset_name {
+        prefs.set_last_view_preset(puzzle_type, preset_name);
+    }
     prefs.needs_save |= changed;
     if changed {
         app.request_redraw_puzzle();
@@ -0,0 +1,93 @@
+use egui::text::{LayoutJob, TextFormat};
+
+use crate::app::App;
+use crate::puzzle::*;
+
+/// Maximum number of suggestions to show for the twist being typed.
+const MAX_SUGGESTIONS: usize = 12;
+
+/// Text field for typing twists using the puzzle's notation, with suggestions
+/// for completing the last twist. Pressing enter applies the twists.
+pub fn twist_entry(ui: &mut egui::Ui, app: &App) {
+    let text_id = unique_id!();
+    let mut text: String = ui.data().get_temp(text_id).unwrap_or_default();
+
+    let mut layouter = |ui: &egui::Ui, s: &str, _wrap_width: f32| {
+        let job = highlight_twists(ui, app.puzzle.notation_scheme(), s);
+        ui.fonts().layout_job(job)
+    };
+    let r = egui::TextEdit::singleline(&mut text)
+        .hint_text("R U R' U'")
+        .layouter(&mut layouter)
+        .show(ui)
+        .response;
+
+    let (twists, errors) = app.puzzle.parse_twists(&text);
+    let r = match errors.first() {
+        Some((_range, msg)) => r.on_hover_text(msg),
+        None => r,
+    };
+    if r.lost_focus() && ui.input().key_pressed(egui::Key::Enter) && errors.is_empty() {
+        for twist in twists {
+            app.event(twist);
+        }
+        text.clear();
+    }
+
+    // Suggest completions for the twist being typed.
+    let last_token_start = text
+        .char_indices()
+        .filter(|&(_, c)| c.is_whitespace() || c == '(' || c == ')')
+        .last()
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let last_token = &text[last_token_start..];
+    let mut completed_text = None;
+    if !last_token.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            for suggestion in app
+                .puzzle
+                .notation_suggestions(last_token)
+                .iter()
+                .take(MAX_SUGGESTIONS)
+            {
+                if ui.small_button(suggestion).clicked() {
+                    completed_text = Some(format!("{}{suggestion} ", &text[..last_token_start]));
+                }
+            }
+        });
+    }
+    if let Some(new_text) = completed_text {
+        text = new_text;
+        r.request_focus();
+    }
+
+    ui.data().insert_temp(text_id, text);
+}
+
+/// Lays out a string of twists with each token colored by its kind, and with
+/// anything that can't be parsed underlined.
+fn highlight_twists(ui: &egui::Ui, notation: &NotationScheme, s: &str) -> LayoutJob {
+    let visuals = ui.visuals();
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let format = |color| TextFormat::simple(font_id.clone(), color);
+
+    let mut job = LayoutJob::default();
+    let mut prev_end = 0;
+    for (range, kind) in notation.tokenize_spanned(s) {
+        job.append(&s[prev_end..range.start], 0.0, format(visuals.text_color()));
+        let token_format = match kind {
+            TokenKind::Grouping => format(visuals.weak_text_color()),
+            TokenKind::Layers => format(visuals.hyperlink_color),
+            TokenKind::Axis => format(visuals.strong_text_color()),
+            TokenKind::Direction => format(visuals.text_color()),
+            TokenKind::Invalid => TextFormat {
+                underline: egui::Stroke::new(1.0, visuals.error_fg_color),
+                ..format(visuals.error_fg_color)
+            },
+        };
+        job.append(&s[range.clone()], 0.0, token_format);
+        prev_end = range.end;
+    }
+    job.append(&s[prev_end..], 0.0, format(visuals.text_color()));
+    job
+}
@@ -1,8 +1,11 @@
+use itertools::Itertools;
+
 use super::components::puzzle_type_menu;
 use super::ext::ResponseExt;
 use super::windows;
 use crate::app::App;
 use crate::commands::Command;
+use crate::puzzle::traits::*;
 
 pub fn build(ui: &mut egui::Ui, app: &mut App) {
     egui::menu::bar(ui, |ui| {
@@ -58,12 +61,71 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
             }
             ui.separator();
             command_button(ui, app, "Full", Command::ScrambleFull);
+            if app.puzzle.ty().supports_random_state() {
+                command_button(ui, app, "Random state", Command::ScrambleRandomState);
+            }
+
+            let cycle_algorithms = app.puzzle.cycle_algorithms();
+            if !cycle_algorithms.is_empty() {
+                ui.separator();
+                for alg in cycle_algorithms {
+                    let text = format!("Only {}s", alg.piece_type);
+                    let cmd = Command::ScramblePieceType(alg.piece_type.to_owned());
+                    command_button(ui, app, &text, cmd);
+                }
+            }
+
+            let patterns = app.puzzle.named_patterns();
+            if !patterns.is_empty() {
+                ui.separator();
+                ui.menu_button("Patterns", |ui| {
+                    let current_pattern = app.puzzle.current_pattern();
+                    for pattern in patterns {
+                        let check = if current_pattern == Some(pattern) {
+                            "✔ "
+                        } else {
+                            ""
+                        };
+                        let text = format!("{check}{}", pattern.name);
+                        let cmd = Command::ApplyPattern(pattern.name.to_owned());
+                        command_button(ui, app, &text, cmd);
+                    }
+                });
+            }
         });
 
         ui.menu_button("Puzzle", |ui| {
             if let Some(ty) = puzzle_type_menu(ui) {
                 app.event(Command::NewPuzzle(ty));
             }
+
+            let quick_access =
+                itertools::chain(&app.prefs.favorite_puzzles, &app.prefs.recent_puzzles)
+                    .copied()
+                    .unique()
+                    .collect_vec();
+            if !quick_access.is_empty() {
+                ui.separator();
+                for ty in quick_access {
+                    let star = if app.prefs.is_favorite_puzzle(ty) {
+                        "★ "
+                    } else {
+                        ""
+                    };
+                    if ui.button(format!("{star}{}", ty.name())).clicked() {
+                        ui.close_menu();
+                        app.event(Command::NewPuzzle(ty));
+                    }
+                }
+            }
+
+            ui.separator();
+            let current = app.puzzle.ty();
+            let mut is_favorite = app.prefs.is_favorite_puzzle(current);
+            if ui.checkbox(&mut is_favorite, "Favorite").changed() {
+                app.prefs.toggle_favorite_puzzle(current);
+                app.prefs.needs_save = true;
+            }
         });
 
         ui.menu_button("Settings", |ui| {
@@ -75,6 +137,13 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
             windows::PUZZLE_KEYBINDS.menu_button_toggle(ui);
             windows::MOUSEBINDS.menu_button_toggle(ui);
 
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                ui.separator();
+                command_button(ui, app, "Import settings…", Command::ImportSettings);
+                command_button(ui, app, "Export settings…", Command::ExportSettings);
+            }
+
             #[cfg(target_arch = "wasm32")]
             {
                 ui.separator();
@@ -93,6 +162,20 @@ pub fn build(ui: &mut egui::Ui, app: &mut App) {
             windows::KEYBIND_SETS.menu_button_toggle(ui);
             windows::MODIFIER_KEYS.menu_button_toggle(ui);
             windows::TIMER.menu_button_toggle(ui);
+            ui.separator();
+            let mut show_piece_labels = app.puzzle.sticker_labels().iter().any(Option::is_some);
+            if ui
+                .checkbox(&mut show_piece_labels, "Label pieces")
+                .changed()
+            {
+                let labels = app
+                    .puzzle
+                    .stickers()
+                    .iter()
+                    .map(|info| show_piece_labels.then(|| info.piece.0.to_string()))
+                    .collect();
+                app.puzzle.set_sticker_labels(labels);
+            }
         });
 
         ui.menu_button("Help", |ui| {
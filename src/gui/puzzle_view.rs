@@ -1,6 +1,9 @@
+use cgmath::Point2;
 use winit::event::ModifiersState;
 
 use crate::app::{App, AppEvent};
+use crate::preferences::ViewPreferences;
+use crate::puzzle::{traits::*, ProjectedStickerGeometry, Sticker};
 
 // experimental
 const ENABLE_CONTEXT_MENU: bool = false;
@@ -39,6 +42,8 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
         cgmath::point2(p.x * 2.0 - 1.0, 1.0 - p.y * 2.0)
     });
 
+    draw_sticker_labels(ui, app, egui_rect);
+
     let popup_state_id = egui::Id::new("puzzle_context_menu_state");
     let mut popup_was_open = ui.data().get_temp(popup_state_id).unwrap_or(false);
     if popup_was_open || app.pressed_modifiers() == ModifiersState::SHIFT {
@@ -81,8 +86,6 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
     // Show debug info for each sticker.
     #[cfg(debug_assertions)]
     if let Some(sticker) = app.puzzle.hovered_sticker() {
-        use crate::puzzle::traits::*;
-
         let mut s = String::new();
         app.puzzle.displayed().sticker_debug_info(&mut s, sticker);
         if !s.is_empty() {
@@ -95,6 +98,97 @@ pub fn build(ui: &mut egui::Ui, app: &mut App, puzzle_texture_id: egui::TextureI
     }
 }
 
+fn draw_sticker_labels(ui: &egui::Ui, app: &mut App, rect: egui::Rect) {
+    if app.puzzle.sticker_labels().iter().all(Option::is_none) {
+        return;
+    }
+
+    let view_prefs = app.puzzle.view_prefs(&app.prefs).into_owned();
+    let geometry = app.puzzle.geometry(&app.prefs);
+    let painter = ui.painter_at(rect);
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let face_colors = app.prefs.colors.face_colors_list(app.puzzle.ty());
+    let is_visible = |sticker: Sticker| {
+        let piece = app.puzzle.info(sticker).piece;
+        app.puzzle.visible_pieces()[piece.0 as usize]
+    };
+    for (sticker, pos, label) in
+        sticker_labels_to_draw(&geometry, app.puzzle.sticker_labels(), is_visible)
+    {
+        let sticker_color = if app.prefs.colors.blindfold {
+            app.prefs.colors.blind_face
+        } else {
+            face_colors[app.puzzle.info(sticker).color.0 as usize]
+        };
+        painter.text(
+            puzzle_to_screen(pos, rect, &view_prefs),
+            egui::Align2::CENTER_CENTER,
+            label,
+            font_id.clone(),
+            label_color(sticker_color),
+        );
+    }
+}
+
+/// Returns black or white, whichever is more readable on top of a sticker.
+fn label_color(sticker_color: egui::Color32) -> egui::Color32 {
+    let rgba = egui::Rgba::from(sticker_color);
+    // Relative luminance, in linear space.
+    let luminance = 0.2126 * rgba.r() + 0.7152 * rgba.g() + 0.0722 * rgba.b();
+    if luminance > 0.18 {
+        egui::Color32::BLACK
+    } else {
+        egui::Color32::WHITE
+    }
+}
+
+/// Returns the text of each sticker label to draw, along with its sticker and
+/// its position in projected puzzle space. Stickers on hidden pieces and stickers facing away
+/// from the camera are skipped.
+fn sticker_labels_to_draw<'a>(
+    geometry: &[ProjectedStickerGeometry],
+    labels: &'a [Option<String>],
+    is_visible: impl Fn(Sticker) -> bool,
+) -> Vec<(Sticker, Point2<f32>, &'a str)> {
+    // Geometry is sorted from back to front, and only front faces are
+    // visible.
+    geometry
+        .iter()
+        .filter(|geom| !geom.front_polygons.is_empty() && is_visible(geom.sticker))
+        .filter_map(|geom| {
+            let label = labels.get(geom.sticker.0 as usize)?.as_deref()?;
+
+            let verts = geom
+                .front_polygons
+                .iter()
+                .flat_map(|polygon| &polygon.verts);
+            let vert_count = verts.clone().count() as f32;
+            let sum = verts.fold(cgmath::vec2(0.0, 0.0), |sum, v| {
+                sum + cgmath::vec2(v.x, v.y)
+            });
+            let centroid = cgmath::point2(sum.x / vert_count, sum.y / vert_count);
+            Some((geom.sticker, centroid, label))
+        })
+        .collect()
+}
+
+/// Converts a point in projected puzzle space to screen space, using the same
+/// scale and alignment as the vertex shader.
+fn puzzle_to_screen(
+    point: Point2<f32>,
+    rect: egui::Rect,
+    view_prefs: &ViewPreferences,
+) -> egui::Pos2 {
+    let pixel_scale = rect.width().min(rect.height()) * view_prefs.scale;
+    let x = point.x * pixel_scale / rect.width() + view_prefs.align_h;
+    let y = point.y * pixel_scale / rect.height() + view_prefs.align_v;
+    // Transform from wgpu to egui coordinates.
+    egui::pos2(
+        rect.left() + (x + 1.0) / 2.0 * rect.width(),
+        rect.top() + (1.0 - y) / 2.0 * rect.height(),
+    )
+}
+
 fn build_puzzle_context_menu(_ui: &mut egui::Ui, _app: &mut App) {
     // let ty = app.puzzle.ty();
 
@@ -156,3 +250,113 @@ fn build_puzzle_context_menu(_ui: &mut egui::Ui, _app: &mut App) {
     //     ui.close_menu();
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle::ClickTwists;
+
+    #[test]
+    fn test_puzzle_to_screen() {
+        let rect = egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(200.0, 100.0));
+        let view_prefs = ViewPreferences {
+            scale: 1.0,
+            align_h: 0.0,
+            align_v: 0.0,
+            ..Default::default()
+        };
+
+        let screen_pos = |x, y| puzzle_to_screen(cgmath::point2(x, y), rect, &view_prefs);
+        assert_eq!(screen_pos(0.0, 0.0), rect.center());
+        // The puzzle is scaled to fit the smaller dimension, and +Y is up.
+        assert_eq!(screen_pos(1.0, 1.0), egui::pos2(160.0, 20.0));
+        assert_eq!(screen_pos(-1.0, -1.0), egui::pos2(60.0, 120.0));
+
+        let aligned = ViewPreferences {
+            align_h: 1.0,
+            ..view_prefs
+        };
+        let screen_pos = puzzle_to_screen(cgmath::point2(0.0, 0.0), rect, &aligned);
+        assert_eq!(screen_pos, egui::pos2(210.0, 70.0));
+    }
+
+    #[test]
+    fn test_sticker_labels_to_draw() {
+        use crate::puzzle::geometry::polygon_from_indices;
+
+        let square = |x: f32| {
+            let verts = [
+                cgmath::point3(x, 0.0, 0.0),
+                cgmath::point3(x + 2.0, 0.0, 0.0),
+                cgmath::point3(x + 2.0, 2.0, 0.0),
+                cgmath::point3(x, 2.0, 0.0),
+            ];
+            polygon_from_indices(&verts, &[0, 1, 2, 3], 1.0, ClickTwists::default())
+        };
+        let sticker_geometry = |sticker, x, is_facing_camera| {
+            let polygons: Box<[_]> = Box::new([square(x)]);
+            let (front_polygons, back_polygons) = if is_facing_camera {
+                (polygons, Box::new([]) as Box<[_]>)
+            } else {
+                (Box::new([]) as Box<[_]>, polygons)
+            };
+            ProjectedStickerGeometry {
+                sticker: Sticker(sticker),
+
+                verts: Box::new([]),
+                min_bound: cgmath::point3(x, 0.0, 0.0),
+                max_bound: cgmath::point3(x + 2.0, 2.0, 0.0),
+
+                front_polygons,
+                back_polygons,
+            }
+        };
+
+        let geometry = [
+            sticker_geometry(0, 0.0, true),
+            sticker_geometry(1, 10.0, true),
+            sticker_geometry(2, 20.0, false),
+            sticker_geometry(3, 30.0, true),
+        ];
+        let labels = [
+            Some("a".to_owned()),
+            None,
+            Some("c".to_owned()),
+            Some("d".to_owned()),
+        ];
+
+        // Unlabeled stickers and stickers facing away are skipped, and labels
+        // are centered on their sticker.
+        let all_visible = sticker_labels_to_draw(&geometry, &labels, |_| true);
+        assert_eq!(
+            all_visible,
+            vec![
+                (Sticker(0), cgmath::point2(1.0, 1.0), "a"),
+                (Sticker(3), cgmath::point2(31.0, 1.0), "d"),
+            ],
+        );
+
+        // Stickers on hidden pieces are skipped.
+        let some_hidden = sticker_labels_to_draw(&geometry, &labels, |s| s != Sticker(3));
+        assert_eq!(
+            some_hidden,
+            vec![(Sticker(0), cgmath::point2(1.0, 1.0), "a")],
+        );
+    }
+
+    #[test]
+    fn test_label_color() {
+        use egui::Color32;
+
+        for light in [
+            Color32::WHITE,
+            Color32::YELLOW,
+            Color32::from_rgb(0, 200, 0),
+        ] {
+            assert_eq!(label_color(light), Color32::BLACK);
+        }
+        for dark in [Color32::BLACK, Color32::BLUE, Color32::from_rgb(128, 0, 0)] {
+            assert_eq!(label_color(dark), Color32::WHITE);
+        }
+    }
+}
@@ -257,6 +257,15 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                     ui.strong(n.to_string())
                 }
                 Command::ScrambleFull => ui.label("Scramble fully"),
+                Command::ScrambleRandomState => ui.label("Scramble to a random state"),
+                Command::ScramblePieceType(piece_type) => {
+                    ui.label("Scramble only");
+                    ui.strong(format!("{piece_type}s"))
+                }
+                Command::ApplyPattern(name) => {
+                    ui.label("Apply pattern");
+                    ui.strong(name)
+                }
 
                 Command::NewPuzzle(ty) => {
                     ui.label("Load new");
@@ -264,6 +273,11 @@ fn draw_key(ui: &mut egui::Ui, app: &mut App, key: KeyMappingCode, rect: egui::R
                     ui.label("puzzle")
                 }
 
+                Command::ImportSettings => ui.label("Import settings"),
+                Command::ExportSettings => ui.label("Export settings"),
+                Command::ImportColors => ui.label("Import colors"),
+                Command::ExportColors => ui.label("Export colors"),
+
                 Command::ToggleBlindfold => ui.label("Toggle blindfold"),
 
                 Command::None => unreachable!(),
@@ -71,6 +71,9 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
         .no_all_except()
         .show(ui, app);
 
+    PieceFilterWidget::new_uppercased("unsolved pieces", app.puzzle.unsolved_pieces())
+        .show(ui, app);
+
     ui.collapsing("Types", |ui| {
         for (i, piece_type) in puzzle_type.piece_types().iter().enumerate() {
             PieceFilterWidget::new_uppercased(
@@ -129,6 +132,17 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
             )
             .show(ui, app);
 
+            let exact_colors = (0..puzzle_type.faces().len())
+                .filter(|&i| selected_colors[i])
+                .map(|i| Face(i as _))
+                .collect::<Vec<_>>();
+            let mut exact_piece = BitVec::repeat(false, puzzle_type.pieces().len());
+            if let Some(piece) = app.puzzle.displayed().find_piece_by_colors(&exact_colors) {
+                exact_piece.set(piece.0 as usize, true);
+            }
+            PieceFilterWidget::new_uppercased("piece with exactly these colors", exact_piece)
+                .show(ui, app);
+
             PieceFilterWidget::new_uppercased(
                 "pieces with only these colors",
                 piece_subset_from_sticker_colors!(puzzle_type, |colors| {
@@ -1,6 +1,6 @@
 use super::Window;
 use crate::app::App;
-use crate::gui::components::reset_button;
+use crate::gui::components::{reset_button, twist_entry};
 use crate::puzzle::*;
 
 pub(crate) const PUZZLE_CONTROLS: Window = Window {
@@ -75,4 +75,9 @@ fn build(ui: &mut egui::Ui, app: &mut App) {
             }
         });
     });
+
+    ui.separator();
+
+    ui.strong("Enter twists");
+    twist_entry(ui, app);
 }
@@ -16,7 +16,7 @@ use crate::puzzle::*;
 
 /// Loads a log file string and returns the puzzle state, along with any
 /// warnings.
-pub fn deserialize(log_file_contents: &str) -> anyhow::Result<(PuzzleController, Vec<String>)> {
+pub fn deserialize(log_file_contents: &str) -> anyhow::Result<(PuzzleController, Vec<Warning>)> {
     if mc4d_compat::is_mc4d_log_file(log_file_contents) {
         let puzzle = mc4d_compat::Mc4dLogFile::from_str(log_file_contents)?
             .to_puzzle()
@@ -41,7 +41,7 @@ pub(crate) fn serialize(
 
 /// Loads a log file and returns the puzzle state, along with any warnings.
 #[cfg(not(target_arch = "wasm32"))]
-pub fn load_file(path: &Path) -> anyhow::Result<(PuzzleController, Vec<String>)> {
+pub fn load_file(path: &Path) -> anyhow::Result<(PuzzleController, Vec<Warning>)> {
     deserialize(&std::fs::read_to_string(path)?)
 }
 
@@ -77,6 +77,42 @@ impl LogFileFormat {
     }
 }
 
+/// How serious a problem encountered while loading a log file is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The file loaded fully, but may not behave exactly as expected.
+    Warning,
+    /// Some of the file's contents could not be loaded and were skipped.
+    Error,
+}
+
+/// Problem encountered while loading a log file that did not prevent it from
+/// loading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub severity: Severity,
+    pub msg: String,
+}
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+impl Warning {
+    fn warning(msg: impl ToString) -> Self {
+        Self {
+            severity: Severity::Warning,
+            msg: msg.to_string(),
+        }
+    }
+    fn error(msg: impl ToString) -> Self {
+        Self {
+            severity: Severity::Error,
+            msg: msg.to_string(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct LogFile {
     version: usize,
@@ -96,12 +132,19 @@ struct LogFile {
         skip_deserializing
     )]
     scramble_length: usize,
+    /// Seed of a full scramble, which can be used to reproduce it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scramble_seed: Option<u64>,
     #[serde(default, skip_deserializing)]
     twist_count: BTreeMap<TwistMetric, usize>,
     #[serde(default, skip_serializing)] // manually serialized
     scramble: String,
     #[serde(default, skip_serializing)] // manually serialized
     twists: String,
+    /// Fields not recognized by this version of Hyperspeedcube, which are
+    /// kept so that saving the file again doesn't lose them.
+    #[serde(flatten)]
+    unknown_fields: BTreeMap<String, serde_yaml::Value>,
 }
 impl fmt::Display for LogFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -129,6 +172,9 @@ impl fmt::Display for LogFile {
 impl LogFile {
     const COMMENT_STRING: &'static str = "# Hyperspeedcube puzzle log";
     const VERSION: usize = 1;
+    /// Fields that are written but never read, which would otherwise be
+    /// mistaken for unknown fields.
+    const WRITE_ONLY_FIELDS: &'static [&'static str] = &["scramble_length", "twist_count"];
 
     fn new(puzzle: &PuzzleController) -> Self {
         let notation = puzzle.notation_scheme();
@@ -141,6 +187,7 @@ impl LogFile {
                 .is_any_piece_hidden()
                 .then(|| puzzle.visible_pieces().to_bitvec()),
             scramble_length: puzzle.scramble().len(),
+            scramble_seed: puzzle.scramble_seed(),
             twist_count: TwistMetric::iter()
                 .map(|metric| (metric, puzzle.twist_count(metric)))
                 .collect(),
@@ -153,6 +200,7 @@ impl LogFile {
                     .iter()
                     .map(|&entry| entry.to_string(notation)),
             ),
+            unknown_fields: puzzle.unknown_log_fields().clone(),
         }
     }
 
@@ -166,10 +214,12 @@ impl LogFile {
     fn scramble(&self) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
         let mut ret_twists = vec![];
         let mut ret_errors = vec![];
-        for twist_str in self.scramble.split_whitespace() {
+        for (index, twist_str) in self.scramble.split_whitespace().enumerate() {
             match twist_str.parse() {
                 Ok(twist) => ret_twists.push(twist),
                 Err(()) => ret_errors.push(TwistParseError {
+                    field: "scramble",
+                    index,
                     twist_str,
                     error_msg: "invalid twist".to_string(),
                 }),
@@ -181,10 +231,12 @@ impl LogFile {
     fn twists(&self, puzzle_type: &dyn PuzzleType) -> (Vec<Twist>, Vec<TwistParseError<'_>>) {
         let mut ret_twists = vec![];
         let mut ret_errors = vec![];
-        for twist_str in self.twists.split_whitespace() {
+        for (index, twist_str) in self.twists.split_whitespace().enumerate() {
             match puzzle_type.notation_scheme().parse_twist(twist_str) {
                 Ok(twist) => ret_twists.push(twist),
                 Err(error_msg) => ret_errors.push(TwistParseError {
+                    field: "twists",
+                    index,
                     twist_str,
                     error_msg,
                 }),
@@ -193,19 +245,19 @@ impl LogFile {
         (ret_twists, ret_errors)
     }
 
-    fn to_puzzle(&self) -> Result<(PuzzleController, Vec<String>)> {
+    fn to_puzzle(&self) -> Result<(PuzzleController, Vec<Warning>)> {
         self.validate()?;
 
         let mut warnings = vec![];
 
         if self.version != LogFile::VERSION {
-            warnings.push(format!(
+            warnings.push(Warning::warning(format!(
                 "This log file was saved using a \
                  different version of Hyperspeedcube \
                  (log file format v{:?}; expected v{:?})",
                 self.version,
                 LogFile::VERSION,
-            ));
+            )));
         }
 
         let puzzle_type = self.puzzle.context("unable to find puzzle type")?;
@@ -218,21 +270,34 @@ impl LogFile {
         }
 
         let (twists, parse_errors) = self.scramble();
-        warnings.extend(parse_errors.iter().map(|e| e.to_string()));
+        warnings.extend(parse_errors.iter().map(Warning::error));
         for twist in twists {
+            // Scramble twists are stored as raw indices, so check them here to
+            // give a more useful warning.
+            if let Err(e) = puzzle_type.check_twist(twist) {
+                warnings.push(Warning::error(format!(
+                    "Error executing scramble twist {twist}: {e}"
+                )));
+                continue;
+            }
             if let Err(e) = ret.twist_no_collapse(twist) {
-                warnings.push(e.to_string());
+                warnings.push(Warning::error(e));
             }
         }
         ret.add_scramble_marker(scramble_state);
+        ret.set_scramble_seed(self.scramble_seed);
 
         let (twists, parse_errors) = self.twists(&puzzle_type);
-        warnings.extend(parse_errors.iter().map(|e| e.to_string()));
+        warnings.extend(parse_errors.iter().map(Warning::error));
         for twist in twists {
             if let Err(e) = ret.twist_no_collapse(twist) {
-                warnings.push(e.to_string());
+                warnings.push(Warning::error(e));
             }
         }
+        let mut unknown_fields = self.unknown_fields.clone();
+        unknown_fields.retain(|k, _| !Self::WRITE_ONLY_FIELDS.contains(&k.as_str()));
+        ret.set_unknown_log_fields(unknown_fields);
+
         ret.skip_twist_animations();
         ret.mark_saved();
 
@@ -242,6 +307,10 @@ impl LogFile {
 
 #[derive(Debug)]
 struct TwistParseError<'a> {
+    /// Name of the field containing the twist.
+    field: &'static str,
+    /// Index of the twist within the field.
+    index: usize,
     twist_str: &'a str,
     error_msg: String,
 }
@@ -249,9 +318,81 @@ impl fmt::Display for TwistParseError<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Error parsing twist {:?}: {}",
-            self.twist_str, self.error_msg,
+            "Error parsing twist {:?} at {}[{}]: {}",
+            self.twist_str, self.field, self.index, self.error_msg,
         )
     }
 }
 impl Error for TwistParseError<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn test_unknown_fields_roundtrip() {
+        let puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let log_file_contents = LogFile::new(&puzzle).to_string() + "future_field:\n  nested: 42\n";
+
+        let (puzzle, _) = deserialize(&log_file_contents).unwrap();
+        let saved = serialize(&puzzle, LogFileFormat::Hsc).unwrap();
+        let (reloaded, _) = deserialize(&saved).unwrap();
+
+        assert_eq!(
+            reloaded.unknown_log_fields().keys().collect_vec(),
+            ["future_field"],
+        );
+        assert_eq!(reloaded.unknown_log_fields(), puzzle.unknown_log_fields());
+        // Write-only fields are not duplicated.
+        assert_eq!(saved.matches("twist_count").count(), 1);
+    }
+
+    #[test]
+    fn test_scramble_seed_roundtrip() {
+        let mut puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let seed = puzzle.scramble_full().unwrap();
+        let saved = serialize(&puzzle, LogFileFormat::Hsc).unwrap();
+        let (reloaded, _) = deserialize(&saved).unwrap();
+        assert_eq!(reloaded.scramble_seed(), Some(seed));
+
+        let mut rescrambled = PuzzleController::new(puzzle.ty());
+        rescrambled.scramble_full_seeded(seed).unwrap();
+        assert_eq!(rescrambled.scramble(), reloaded.scramble());
+
+        // Other scrambles don't have a seed.
+        puzzle.scramble_n(5).unwrap();
+        let saved = serialize(&puzzle, LogFileFormat::Hsc).unwrap();
+        assert!(!saved.contains("scramble_seed"));
+    }
+
+    #[test]
+    fn test_warning_severity() {
+        let puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let log_file_contents = LogFile::new(&puzzle)
+            .to_string()
+            .replace("version: 1", "version: 2")
+            + "twists: R Q\n";
+
+        let (_, warnings) = deserialize(&log_file_contents).unwrap();
+        assert_eq!(
+            warnings.iter().map(|w| w.severity).collect_vec(),
+            [Severity::Warning, Severity::Error],
+        );
+    }
+
+    #[test]
+    fn test_warning_location() {
+        let puzzle = PuzzleController::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        let log_file_contents = LogFile::new(&puzzle).to_string() + "twists: R U Q F\n";
+
+        let (_, warnings) = deserialize(&log_file_contents).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0].msg.contains("twists[2]"),
+            "missing location in {:?}",
+            warnings[0].msg,
+        );
+    }
+}
@@ -486,6 +486,10 @@ async fn run() {
                     }
                     // TODO: display framerate somewhere
                     printlnd!("FPS: {}", last_fps);
+                    printlnd!(
+                        "Puzzle memory: {} KiB",
+                        app.puzzle.estimated_memory_bytes() / 1024,
+                    );
                 }
             }
 
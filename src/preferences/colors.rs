@@ -1,3 +1,4 @@
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::ops::{Index, IndexMut};
@@ -41,7 +42,7 @@ impl IndexMut<(PuzzleTypeEnum, Face)> for ColorPreferences {
 }
 
 // TODO: rename this type and use it for all colors. also impl display
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(transparent)]
 pub struct FaceColor(#[serde(with = "hex_color")] pub egui::Color32);
 
@@ -56,4 +57,66 @@ impl ColorPreferences {
             })
             .collect()
     }
+
+    /// Serializes the face colors for a puzzle family to a standalone string
+    /// that can be shared separately from the preferences file.
+    pub fn export_face_colors(&self, ty: PuzzleTypeEnum) -> Result<String> {
+        let scheme = FaceColorScheme {
+            puzzle_family: ty.family_internal_name().to_owned(),
+            faces: self.faces[ty].clone(),
+        };
+        Ok(serde_yaml::to_string(&scheme)?)
+    }
+    /// Loads face colors for a puzzle family from a string produced by
+    /// `export_face_colors()`. Faces not mentioned in the string are left
+    /// unchanged.
+    pub fn import_face_colors(&mut self, ty: PuzzleTypeEnum, s: &str) -> Result<()> {
+        let scheme: FaceColorScheme = serde_yaml::from_str(s)?;
+        if scheme.puzzle_family != ty.family_internal_name() {
+            bail!(
+                "color scheme is for {}, not {}",
+                scheme.puzzle_family,
+                ty.family_internal_name(),
+            );
+        }
+        let face_colors = &mut self.faces[ty];
+        for face in ty.faces() {
+            if let Some(color) = scheme.faces.get(face.symbol) {
+                face_colors.insert(face.symbol.to_owned(), color.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Face colors for one puzzle family, in a format that can be saved to its own
+/// file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct FaceColorScheme {
+    puzzle_family: String,
+    faces: BTreeMap<String, FaceColor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_face_colors_roundtrip() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut prefs = ColorPreferences::default();
+        prefs[(ty, Face(0))] = egui::Color32::from_rgb(255, 0, 0);
+        prefs[(ty, Face(1))] = egui::Color32::from_rgb(0, 128, 255);
+
+        let exported = prefs.export_face_colors(ty).unwrap();
+
+        let mut new_prefs = ColorPreferences::default();
+        new_prefs.import_face_colors(ty, &exported).unwrap();
+        assert_eq!(new_prefs.faces[ty], prefs.faces[ty]);
+        assert_eq!(new_prefs.face_colors_list(ty), prefs.face_colors_list(ty));
+
+        // Color schemes from other puzzle families are rejected.
+        let other_ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        assert!(new_prefs.import_face_colors(other_ty, &exported).is_err());
+    }
 }
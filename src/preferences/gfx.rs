@@ -1,17 +1,28 @@
 use instant::Duration;
 use serde::{Deserialize, Serialize};
 
+/// Maximum render scale. The render is downsampled by bilinear sampling of the
+/// texture, which only averages 2x2 texels, so higher scales would skip texels
+/// and alias instead of supersampling.
+pub const MAX_RENDER_SCALE: f32 = 2.0;
+/// Minimum render scale.
+pub const MIN_RENDER_SCALE: f32 = 0.25;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(default)]
 pub struct GfxPreferences {
     pub fps_limit: usize,
     pub msaa: bool,
+    /// Resolution of the puzzle render relative to its size on screen,
+    /// independent of DPI. Values greater than 1 supersample the puzzle.
+    pub render_scale: f32,
 }
 impl Default for GfxPreferences {
     fn default() -> Self {
         Self {
             fps_limit: 60,
             msaa: true,
+            render_scale: 1.0,
         }
     }
 }
@@ -29,4 +40,58 @@ impl GfxPreferences {
             1
         }
     }
+
+    /// Returns the render scale, clamped to a sane range.
+    pub fn render_scale(&self) -> f32 {
+        if self.render_scale.is_finite() {
+            self.render_scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE)
+        } else {
+            1.0
+        }
+    }
+    /// Returns the size of the texture to render the puzzle to, given the size
+    /// of the puzzle view on screen in physical pixels and the maximum texture
+    /// dimension supported by the device. The aspect ratio is preserved even if
+    /// the size must be reduced to fit within the maximum.
+    pub fn render_target_size(
+        &self,
+        (width, height): (u32, u32),
+        max_dimension: u32,
+    ) -> (u32, u32) {
+        let mut scale = self.render_scale();
+        let largest_dimension = width.max(height) as f32 * scale;
+        if largest_dimension > max_dimension as f32 {
+            scale *= max_dimension as f32 / largest_dimension;
+        }
+        let scale_dimension = |n: u32| ((n as f32 * scale).round() as u32).min(max_dimension);
+        (scale_dimension(width), scale_dimension(height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_target_size() {
+        const MAX: u32 = 8192;
+
+        let mut gfx = GfxPreferences::default();
+        assert_eq!(gfx.render_target_size((640, 480), MAX), (640, 480));
+
+        gfx.render_scale = 2.0;
+        assert_eq!(gfx.render_target_size((640, 480), MAX), (1280, 960));
+
+        gfx.render_scale = 1000.0;
+        let max = (640.0 * MAX_RENDER_SCALE) as u32;
+        assert_eq!(gfx.render_target_size((640, 0), MAX), (max, 0));
+
+        gfx.render_scale = f32::NAN;
+        assert_eq!(gfx.render_target_size((640, 480), MAX), (640, 480));
+
+        // The device limit shrinks both dimensions, preserving aspect ratio.
+        gfx.render_scale = 2.0;
+        assert_eq!(gfx.render_target_size((2560, 1440), 4096), (4096, 2304));
+        assert_eq!(gfx.render_target_size((1024, 512), 2048), (2048, 1024));
+    }
 }
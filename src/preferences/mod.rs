@@ -37,11 +37,15 @@ pub use opacity::*;
 pub use outlines::*;
 #[cfg(not(target_arch = "wasm32"))]
 use persist_local as persist;
+#[cfg(not(target_arch = "wasm32"))]
+pub use persist_local::cache_dir;
 #[cfg(target_arch = "wasm32")]
 use persist_web as persist;
 pub use view::*;
 
 const PREFS_FILE_FORMAT: config::FileFormat = config::FileFormat::Yaml;
+/// Maximum number of recently-used puzzles to remember.
+pub const MAX_RECENT_PUZZLES: usize = 8;
 const DEFAULT_PREFS_STR: &str = include_str!("default.yaml");
 
 lazy_static! {
@@ -64,6 +68,11 @@ pub struct Preferences {
 
     pub show_welcome_at_startup: bool,
 
+    /// Puzzles marked as favorites.
+    pub favorite_puzzles: Vec<PuzzleTypeEnum>,
+    /// Recently-used puzzles, most recent first.
+    pub recent_puzzles: Vec<PuzzleTypeEnum>,
+
     #[cfg(target_arch = "wasm32")]
     pub use_clipboard_fallback: bool,
 
@@ -76,6 +85,8 @@ pub struct Preferences {
 
     pub view_3d: WithPresets<ViewPreferences>,
     pub view_4d: WithPresets<ViewPreferences>,
+    /// Name of the view preset most recently loaded for each puzzle.
+    pub last_view_preset: PerPuzzle<Option<String>>,
 
     pub colors: ColorPreferences,
 
@@ -129,6 +140,7 @@ impl Preferences {
 
             // Clear empty entries.
             self.piece_filters.map.retain(|_k, v| !v.is_empty());
+            self.last_view_preset.map.retain(|_k, v| v.is_some());
 
             // Set version number.
             self.version = migration::LATEST_VERSION;
@@ -142,6 +154,55 @@ impl Preferences {
         }
     }
 
+    /// Records that a puzzle was opened, either as a new puzzle or from a log
+    /// file, and loads the view preset most recently used with it. Returns the
+    /// previous view settings if a preset was loaded.
+    pub fn open_puzzle(&mut self, ty: PuzzleTypeEnum) -> Option<ViewPreferences> {
+        self.add_recent_puzzle(ty);
+        self.needs_save = true;
+        self.load_last_view_preset(ty)
+    }
+    /// Moves a puzzle to the front of the list of recently-used puzzles.
+    pub fn add_recent_puzzle(&mut self, ty: PuzzleTypeEnum) {
+        self.recent_puzzles.retain(|&t| t != ty);
+        self.recent_puzzles.insert(0, ty);
+        self.recent_puzzles.truncate(MAX_RECENT_PUZZLES);
+    }
+    /// Returns whether a puzzle is marked as a favorite.
+    pub fn is_favorite_puzzle(&self, ty: PuzzleTypeEnum) -> bool {
+        self.favorite_puzzles.contains(&ty)
+    }
+    /// Adds or removes a puzzle from the list of favorites.
+    pub fn toggle_favorite_puzzle(&mut self, ty: PuzzleTypeEnum) {
+        if self.is_favorite_puzzle(ty) {
+            self.favorite_puzzles.retain(|&t| t != ty);
+        } else {
+            self.favorite_puzzles.push(ty);
+        }
+    }
+
+    /// Serializes all preferences except machine-specific ones (such as the
+    /// path of the open log file), for moving them to another computer.
+    pub fn export_bundle(&self) -> anyhow::Result<String> {
+        let mut bundle = self.clone();
+        bundle.log_file = None;
+        bundle.version = migration::LATEST_VERSION;
+        Ok(serde_yaml::to_string(&bundle)?)
+    }
+    /// Replaces all preferences except machine-specific ones with those from
+    /// a string produced by `export_bundle()`.
+    pub fn import_bundle(&mut self, s: &str) -> anyhow::Result<()> {
+        let mut new_prefs = config::Config::builder()
+            .add_source(config::File::from_str(DEFAULT_PREFS_STR, PREFS_FILE_FORMAT))
+            .add_source(config::File::from_str(s, PREFS_FILE_FORMAT))
+            .build()
+            .and_then(migration::try_deserialize)?;
+        new_prefs.log_file = self.log_file.take();
+        new_prefs.needs_save = true;
+        *self = new_prefs;
+        Ok(())
+    }
+
     pub fn view(&self, ty: impl PuzzleType) -> &ViewPreferences {
         match ty.projection_type() {
             ProjectionType::_3D => &self.view_3d.current,
@@ -158,6 +219,27 @@ impl Preferences {
             ProjectionType::_4D => &mut self.view_4d,
         }
     }
+
+    /// Records that a view preset was loaded for a puzzle, so that it can be
+    /// loaded again the next time the puzzle is opened.
+    pub fn set_last_view_preset(&mut self, ty: PuzzleTypeEnum, preset_name: String) {
+        self.last_view_preset[ty] = Some(preset_name);
+    }
+    /// Loads the view preset most recently used with a puzzle, if there is one
+    /// and it still exists. Returns the previous view settings if the preset
+    /// was loaded.
+    pub fn load_last_view_preset(&mut self, ty: PuzzleTypeEnum) -> Option<ViewPreferences> {
+        let preset_name = self.last_view_preset[ty].clone()?;
+        let presets = self.view_presets(ty);
+        let preset = presets
+            .presets
+            .iter()
+            .find(|p| p.preset_name == preset_name)?
+            .clone();
+        let old = std::mem::replace(&mut presets.current, preset.value.clone());
+        presets.active_preset = Some(preset);
+        Some(old)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
@@ -308,3 +390,117 @@ pub struct PieceFilter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hidden_opacity: Option<f32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_puzzles() {
+        let mut prefs = Preferences::default();
+        let types = (2..=9).map(|layer_count| PuzzleTypeEnum::Rubiks3D { layer_count });
+        for ty in types.clone() {
+            prefs.add_recent_puzzle(ty);
+        }
+        prefs.add_recent_puzzle(PuzzleTypeEnum::Rubiks4D { layer_count: 3 });
+        prefs.add_recent_puzzle(PuzzleTypeEnum::Rubiks3D { layer_count: 5 });
+
+        assert_eq!(prefs.recent_puzzles.len(), MAX_RECENT_PUZZLES);
+        assert_eq!(
+            prefs.recent_puzzles[..3],
+            [
+                PuzzleTypeEnum::Rubiks3D { layer_count: 5 },
+                PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+                PuzzleTypeEnum::Rubiks3D { layer_count: 9 },
+            ],
+        );
+        // The oldest puzzles are forgotten first.
+        assert!(!prefs
+            .recent_puzzles
+            .contains(&PuzzleTypeEnum::Rubiks3D { layer_count: 2 }));
+    }
+
+    #[test]
+    fn test_open_puzzle_from_log_file() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 2 };
+        let log_file_contents = crate::logfile::serialize(
+            &crate::puzzle::PuzzleController::new(ty),
+            crate::logfile::LogFileFormat::Hsc,
+        )
+        .unwrap();
+        let (puzzle, _) = crate::logfile::deserialize(&log_file_contents).unwrap();
+
+        let mut prefs = Preferences::default();
+        prefs.add_recent_puzzle(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        assert_eq!(prefs.open_puzzle(puzzle.ty()), None);
+        assert_eq!(prefs.recent_puzzles[0], ty);
+        assert!(prefs.needs_save);
+    }
+
+    #[test]
+    fn test_last_view_preset() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 4 };
+        let other_ty = PuzzleTypeEnum::Rubiks3D { layer_count: 5 };
+
+        let mut prefs = Preferences::default();
+        let mut preset = Preset {
+            preset_name: "Close up".to_string(),
+            value: ViewPreferences::default(),
+        };
+        preset.value.scale = 3.0;
+        prefs.view_3d.presets.push(preset.clone());
+
+        // Puzzles that have never had a preset loaded are left alone.
+        assert_eq!(prefs.load_last_view_preset(ty), None);
+
+        prefs.set_last_view_preset(ty, preset.preset_name.clone());
+        let mut prefs: Preferences =
+            serde_yaml::from_str(&serde_yaml::to_string(&prefs).unwrap()).unwrap();
+        assert!(prefs.open_puzzle(ty).is_some());
+        assert_eq!(prefs.view(ty).scale, 3.0);
+        assert_eq!(prefs.view_3d.active_preset, Some(preset));
+        assert_eq!(prefs.load_last_view_preset(other_ty), None);
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+
+        let mut prefs = DEFAULT_PREFS.clone();
+        prefs.log_file = Some(PathBuf::from("my_solve.hsc"));
+        prefs.gfx.render_scale = 2.0;
+        prefs.colors[(ty, crate::puzzle::Face(0))] = egui::Color32::from_rgb(1, 2, 3);
+        prefs.toggle_favorite_puzzle(ty);
+
+        let bundle = prefs.export_bundle().unwrap();
+        assert!(!bundle.contains("my_solve.hsc"));
+
+        let mut other = DEFAULT_PREFS.clone();
+        other.log_file = Some(PathBuf::from("other.hsc"));
+        other.import_bundle(&bundle).unwrap();
+
+        assert_eq!(other.log_file, Some(PathBuf::from("other.hsc")));
+        assert_eq!(other.gfx.render_scale, 2.0);
+        assert_eq!(
+            other.colors.face_colors_list(ty),
+            prefs.colors.face_colors_list(ty),
+        );
+        assert_eq!(other.favorite_puzzles, [ty]);
+        assert!(other.needs_save);
+    }
+
+    #[test]
+    fn test_favorite_puzzles() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let mut prefs = Preferences::default();
+        prefs.toggle_favorite_puzzle(ty);
+        assert!(prefs.is_favorite_puzzle(ty));
+
+        let roundtripped: Preferences =
+            serde_yaml::from_str(&serde_yaml::to_string(&prefs).unwrap()).unwrap();
+        assert_eq!(roundtripped.favorite_puzzles, [ty]);
+
+        prefs.toggle_favorite_puzzle(ty);
+        assert!(!prefs.is_favorite_puzzle(ty));
+    }
+}
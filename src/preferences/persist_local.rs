@@ -5,6 +5,7 @@ use std::path::PathBuf;
 
 const PREFS_FILE_NAME: &str = "hyperspeedcube";
 const PREFS_FILE_EXTENSION: &str = "yaml";
+const CACHE_DIR_NAME: &str = "cache";
 
 // File paths
 lazy_static! {
@@ -48,6 +49,16 @@ lazy_static! {
         p.push(format!("{}.{}", PREFS_FILE_NAME, PREFS_FILE_EXTENSION));
         Ok(p)
     };
+    static ref CACHE_DIR_PATH: Result<PathBuf, PrefsError> = {
+        if *NONPORTABLE {
+            match &*PROJECT_DIRS {
+                Some(proj_dirs) => Ok(proj_dirs.cache_dir().to_owned()),
+                None => Err(PrefsError::NoPreferencesPath),
+            }
+        } else {
+            Ok(LOCAL_DIR.clone()?.join(CACHE_DIR_NAME))
+        }
+    };
 
 }
 
@@ -66,6 +77,13 @@ pub fn user_config_source() -> Result<impl config::Source, PrefsError> {
         .map(|path| config::File::from(path.as_ref()))
 }
 
+/// Returns the directory for files that can be regenerated if they are
+/// deleted. This is next to the executable unless the preferences are
+/// nonportable.
+pub fn cache_dir() -> Result<PathBuf, PrefsError> {
+    CACHE_DIR_PATH.clone()
+}
+
 pub fn save(prefs_data: &impl Serialize) -> anyhow::Result<()> {
     let path = PREFS_FILE_PATH.as_ref()?;
     if let Some(p) = path.parent() {
@@ -29,6 +29,9 @@ pub struct ViewPreferences {
 
     pub face_spacing: f32,
     pub sticker_spacing: f32,
+    /// How far to move each piece away from the center of the puzzle, as a
+    /// fraction of its distance from the center.
+    pub piece_explode: f32,
 
     pub outline_thickness: f32,
 
@@ -53,6 +56,7 @@ impl Default for ViewPreferences {
 
             face_spacing: 0.0,
             sticker_spacing: 0.0,
+            piece_explode: 0.0,
 
             show_frontfaces: true,
             show_backfaces: true,
@@ -103,6 +107,7 @@ impl ViewPreferences {
             clip_4d: if t < 0.5 { self.clip_4d } else { rhs.clip_4d },
             face_spacing: crate::util::mix(self.face_spacing, rhs.face_spacing, t),
             sticker_spacing: crate::util::mix(self.sticker_spacing, rhs.sticker_spacing, t),
+            piece_explode: crate::util::mix(self.piece_explode, rhs.piece_explode, t),
             outline_thickness: crate::util::mix(self.outline_thickness, rhs.outline_thickness, t),
             light_ambient: crate::util::mix(self.light_ambient, rhs.light_ambient, t),
             light_directional: crate::util::mix(self.light_directional, rhs.light_directional, t),
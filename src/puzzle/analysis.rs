@@ -0,0 +1,459 @@
+//! Algorithms for comparing and analyzing puzzle states.
+
+use bitvec::vec::BitVec;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+
+/// Parity of a permutation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+impl Puzzle {
+    /// Returns the color of the sticker at each location on the puzzle, in a
+    /// fixed order of locations.
+    pub(super) fn color_layout(&self) -> Vec<u16> {
+        (0..self.stickers().len() as _)
+            .map(Sticker)
+            .map(|sticker| {
+                let (face, location) = self.sticker_location(sticker);
+                ((face.0, location), self.info(sticker).color.0 as u16)
+            })
+            .sorted()
+            .map(|(_location, color)| color)
+            .collect()
+    }
+
+    /// Returns every distinct coloring of the puzzle that can be reached using
+    /// only whole-puzzle rotations, including the current one.
+    pub(super) fn orientations(&self) -> Vec<Puzzle> {
+        // Whole-puzzle rotations generate the symmetry group of the puzzle.
+        let rotations = itertools::iproduct!(
+            (0..self.twist_axes().len() as _).map(TwistAxis),
+            (0..self.twist_directions().len() as _).map(TwistDirection)
+        )
+        .map(|(axis, direction)| Twist {
+            axis,
+            direction,
+            layers: self.all_layers(),
+        })
+        .collect_vec();
+
+        // Find every coloring reachable using rotations, and pick the least
+        // one. Rotations act on colorings the same regardless of the
+        // underlying piece states, so it's safe to deduplicate by coloring.
+        let mut seen = HashSet::new();
+        let mut ret = vec![self.clone()];
+        seen.insert(self.color_layout());
+        let mut i = 0;
+        while let Some(state) = ret.get(i).cloned() {
+            for &twist in &rotations {
+                let mut new_state = state.clone();
+                if new_state.twist(twist).is_err() {
+                    continue;
+                }
+                if seen.insert(new_state.color_layout()) {
+                    ret.push(new_state);
+                }
+            }
+            i += 1;
+        }
+        ret
+    }
+
+    /// Returns a signature of the coloring of the puzzle that does not depend
+    /// on the orientation of the whole puzzle. Two states have the same
+    /// signature if and only if one can be turned into the other using only
+    /// whole-puzzle rotations.
+    pub fn color_signature(&self) -> Vec<u16> {
+        self.orientations()
+            .iter()
+            .map(|state| state.color_layout())
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// Returns the color that belongs on each face in the current orientation
+    /// of the whole puzzle. This is the color of the middle center sticker of
+    /// the face if there is one, and the most common color on the face
+    /// otherwise.
+    fn face_colors(&self) -> Vec<Face> {
+        let face_count = self.faces().len();
+        let layer_count = self.layer_count();
+        let middle = (layer_count - 1) / 2;
+
+        let mut color_counts = vec![vec![0_usize; face_count]; face_count];
+        let mut center_colors = vec![None; face_count];
+        for sticker in (0..self.stickers().len() as _).map(Sticker) {
+            let (face, location) = self.sticker_location(sticker);
+            let color = self.info(sticker).color;
+            color_counts[face.0 as usize][color.0 as usize] += 1;
+
+            // The middle center is in the middle layer along every axis
+            // except the one perpendicular to its face.
+            let is_middle_center = layer_count % 2 == 1
+                && layer_count > 1
+                && location.iter().filter(|&&x| x != middle).count() == 1;
+            if is_middle_center {
+                center_colors[face.0 as usize] = Some(color);
+            }
+        }
+
+        center_colors
+            .into_iter()
+            .zip(color_counts)
+            .map(|(center_color, counts)| {
+                center_color.unwrap_or_else(|| {
+                    let most_common = counts.iter().position_max().unwrap_or(0);
+                    Face(most_common as _)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the set of pieces that have at least one sticker on the wrong
+    /// face, ignoring the orientation of the whole puzzle. Unlike `diff()`,
+    /// this only looks at colors, so pieces that are twisted in place without
+    /// any visible change (such as centers) are considered solved.
+    pub fn unsolved_pieces(&self) -> BitVec {
+        let face_colors = self.face_colors();
+        (0..self.pieces().len() as _)
+            .map(Piece)
+            .map(|piece| {
+                self.info(piece).stickers.iter().any(|&sticker| {
+                    let (face, _location) = self.sticker_location(sticker);
+                    face_colors[face.0 as usize] != self.info(sticker).color
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the fraction of pieces (from 0.0 to 1.0) that have at least
+    /// one sticker on the wrong face, ignoring the orientation of the whole
+    /// puzzle. This is a rough measure of how well-scrambled the puzzle is.
+    ///
+    /// This only looks at each sticker once, so it's cheap enough to call
+    /// even on large puzzles.
+    pub fn scramble_quality(&self) -> f32 {
+        if self.pieces().is_empty() {
+            return 0.0;
+        }
+        let displaced = self.unsolved_pieces().count_ones();
+        displaced as f32 / self.pieces().len() as f32
+    }
+
+    /// Returns the set of pieces that are in a different position or
+    /// orientation in `other`, which must be the same type of puzzle.
+    pub fn diff(&self, other: &Puzzle) -> Result<BitVec, &'static str> {
+        if self.ty() != other.ty() {
+            return Err("cannot compare different puzzle types");
+        }
+        match (self, other) {
+            (Puzzle::Rubiks3D(a), Puzzle::Rubiks3D(b)) => Ok(a.diff(b)),
+            (Puzzle::Rubiks4D(a), Puzzle::Rubiks4D(b)) => Ok(a.diff(b)),
+            _ => Err("cannot compare different puzzle types"),
+        }
+    }
+
+    /// Returns a rough estimate of the number of bytes of memory used by the
+    /// puzzle state. The puzzle description is shared by every puzzle of the
+    /// same type and is never freed, so it isn't counted.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+
+        let piece_state_size = match self {
+            Puzzle::Rubiks3D(_) => size_of::<rubiks_3d::PieceState>(),
+            Puzzle::Rubiks4D(_) => size_of::<rubiks_4d::PieceState>(),
+        };
+
+        size_of::<Self>() + self.pieces().len() * piece_state_size
+    }
+
+    /// Returns the parity of the permutation of pieces relative to the solved
+    /// state, ignoring piece orientation. On puzzles where every twist is an
+    /// even permutation, an odd state can't be solved; see
+    /// `PuzzleTypeEnum::is_parity_constrained()`.
+    pub fn permutation_parity(&self) -> Parity {
+        let piece_location = |puzzle: &Puzzle, piece: Piece| {
+            let &sticker = puzzle.info(piece).stickers.first()?;
+            Some(puzzle.sticker_location(sticker).1)
+        };
+
+        let solved = Puzzle::new(self.ty());
+        let home_locations: HashMap<_, Piece> = (0..solved.pieces().len() as _)
+            .map(Piece)
+            .filter_map(|piece| Some((piece_location(&solved, piece)?, piece)))
+            .collect();
+        // Piece whose home location is where `piece` is now. Pieces without
+        // stickers are assumed to stay put.
+        let next = |piece: Piece| {
+            piece_location(self, piece)
+                .and_then(|location| home_locations.get(&location).copied())
+                .unwrap_or(piece)
+        };
+
+        // A cycle of even length is an odd permutation.
+        let mut visited = vec![false; self.pieces().len()];
+        let mut parity = Parity::Even;
+        for start in (0..self.pieces().len() as _).map(Piece) {
+            let mut cycle_len = 0;
+            let mut piece = start;
+            while !visited[piece.0 as usize] {
+                visited[piece.0 as usize] = true;
+                cycle_len += 1;
+                piece = next(piece);
+            }
+            if cycle_len % 2 == 0 && cycle_len > 0 {
+                parity = match parity {
+                    Parity::Even => Parity::Odd,
+                    Parity::Odd => Parity::Even,
+                };
+            }
+        }
+        parity
+    }
+
+    /// Returns the piece whose stickers have exactly the given colors, in any
+    /// order. Piece IDs do not change when the puzzle is twisted, so the
+    /// piece's current location can be found using `sticker_location()`.
+    pub fn find_piece_by_colors(&self, colors: &[Face]) -> Option<Piece> {
+        let target = colors.iter().map(|color| color.0).sorted().collect_vec();
+        (0..self.pieces().len() as _).map(Piece).find(|&piece| {
+            let piece_colors = self
+                .info(piece)
+                .stickers
+                .iter()
+                .map(|&sticker| self.info(sticker).color.0)
+                .sorted()
+                .collect_vec();
+            piece_colors == target
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(puzzle: &mut Puzzle, twists: &str) {
+        for twist_str in puzzle.split_twists_string(twists).map(|m| m.as_str()) {
+            let twist = puzzle.notation_scheme().parse_twist(twist_str).unwrap();
+            puzzle.twist(twist).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_color_signature_ignores_rotation() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+
+        let mut pattern = Puzzle::new(ty);
+        apply(&mut pattern, "R U R' U'");
+        let mut rotated = pattern.clone();
+        apply(&mut rotated, "x y2 z'");
+        assert_ne!(pattern, rotated);
+        assert_eq!(pattern.color_signature(), rotated.color_signature());
+
+        let mut solved = Puzzle::new(ty);
+        assert_ne!(pattern.color_signature(), solved.color_signature());
+        apply(&mut solved, "y");
+        assert_eq!(solved.color_signature(), Puzzle::new(ty).color_signature());
+    }
+
+    #[test]
+    fn test_diff() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let solved = Puzzle::new(ty);
+            assert!(solved.diff(&solved).unwrap().not_any());
+
+            let twist = Twist {
+                axis: TwistAxis(0),
+                direction: TwistDirection(0),
+                layers: LayerMask(1),
+            };
+            let mut twisted = solved.clone();
+            twisted.twist(twist).unwrap();
+
+            let diff = solved.diff(&twisted).unwrap();
+            let moved_pieces = solved.pieces_affected_by_twist(twist);
+            assert_eq!(diff.count_ones(), moved_pieces.len());
+            for piece in moved_pieces {
+                assert!(diff[piece.0 as usize]);
+            }
+        }
+
+        let other = Puzzle::new(PuzzleTypeEnum::Rubiks3D { layer_count: 2 });
+        let this = Puzzle::new(PuzzleTypeEnum::Rubiks3D { layer_count: 3 });
+        assert!(this.diff(&other).is_err());
+    }
+
+    #[test]
+    fn test_unsolved_pieces() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = Puzzle::new(ty);
+        assert!(puzzle.unsolved_pieces().not_any());
+
+        // Whole-puzzle rotations don't count.
+        apply(&mut puzzle, "x");
+        assert!(puzzle.unsolved_pieces().not_any());
+        assert!(Puzzle::new(ty).diff(&puzzle).unwrap().any());
+
+        // `R` moves every piece on the right face except the center, which
+        // only turns in place.
+        apply(&mut puzzle, "x' R");
+        assert_eq!(puzzle.unsolved_pieces().count_ones(), 8);
+
+        // This cycles three edges and turns centers without any visible
+        // change to them.
+        let mut puzzle = Puzzle::new(ty);
+        apply(&mut puzzle, "M2 U M U2 M' U M2");
+        assert_eq!(puzzle.unsolved_pieces().count_ones(), 3);
+        assert!(Puzzle::new(ty).diff(&puzzle).unwrap().count_ones() > 3);
+    }
+
+    #[test]
+    fn test_find_piece_by_colors() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = Puzzle::new(ty);
+        let face = |symbol| Face(ty.faces().iter().position(|f| f.symbol == symbol).unwrap() as u8);
+        let (u, f, l) = (face("U"), face("F"), face("L"));
+
+        let edge = puzzle.find_piece_by_colors(&[f, u]).unwrap();
+        let sticker_faces = |puzzle: &Puzzle| {
+            puzzle
+                .info(edge)
+                .stickers
+                .iter()
+                .map(|&s| puzzle.sticker_location(s).0 .0)
+                .sorted()
+                .collect_vec()
+        };
+        assert_eq!(
+            sticker_faces(&puzzle),
+            vec![u.0, f.0].into_iter().sorted().collect_vec()
+        );
+
+        // `U` moves the UF edge to UL.
+        apply(&mut puzzle, "U");
+        assert_eq!(puzzle.find_piece_by_colors(&[u, f]), Some(edge));
+        assert_eq!(
+            sticker_faces(&puzzle),
+            vec![u.0, l.0].into_iter().sorted().collect_vec()
+        );
+
+        // There is no piece with both U and D stickers.
+        assert_eq!(puzzle.find_piece_by_colors(&[u, face("D")]), None);
+    }
+
+    #[test]
+    fn test_checkerboard_pattern() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = Puzzle::new(ty);
+        let checkerboard = ty
+            .named_patterns()
+            .iter()
+            .find(|p| p.name == "Checkerboard")
+            .unwrap();
+        apply(&mut puzzle, checkerboard.twists);
+
+        // Every edge sticker has a different color from the center and
+        // corners on the same face.
+        for sticker in (0..puzzle.stickers().len() as _).map(Sticker) {
+            let (face, _location) = puzzle.sticker_location(sticker);
+            let is_edge = puzzle.info(puzzle.info(sticker).piece).stickers.len() == 2;
+            assert_eq!(puzzle.info(sticker).color == face, !is_edge);
+        }
+    }
+
+    #[test]
+    fn test_estimated_memory_bytes() {
+        let small = Puzzle::new(PuzzleTypeEnum::Rubiks3D { layer_count: 2 });
+        let large = Puzzle::new(PuzzleTypeEnum::Rubiks4D { layer_count: 5 });
+        assert!(large.estimated_memory_bytes() > small.estimated_memory_bytes());
+    }
+
+    #[test]
+    fn test_scramble_quality() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = Puzzle::new(ty);
+        assert_eq!(puzzle.scramble_quality(), 0.0);
+
+        // Whole-puzzle rotations don't count.
+        apply(&mut puzzle, "x y");
+        assert_eq!(puzzle.scramble_quality(), 0.0);
+
+        apply(&mut puzzle, "R");
+        let one_move = puzzle.scramble_quality();
+        assert!(one_move > 0.0);
+
+        let mut full = PuzzleController::new(ty);
+        full.scramble_full().unwrap();
+        assert!(full.scramble_quality() > one_move);
+
+        // Puzzles without centers are oriented by the most common colors.
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let mut puzzle = Puzzle::new(ty);
+        apply(&mut puzzle, "x y");
+        assert_eq!(puzzle.scramble_quality(), 0.0);
+        apply(&mut puzzle, "R");
+        assert!(puzzle.scramble_quality() > 0.0);
+    }
+
+    #[test]
+    fn test_permutation_parity() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let mut puzzle = Puzzle::new(ty);
+        assert_eq!(puzzle.permutation_parity(), Parity::Even);
+        // A quarter turn cycles four pieces, which is an odd permutation.
+        apply(&mut puzzle, "U");
+        assert_eq!(puzzle.permutation_parity(), Parity::Odd);
+        apply(&mut puzzle, "R");
+        assert_eq!(puzzle.permutation_parity(), Parity::Even);
+        apply(&mut puzzle, "F2");
+        assert_eq!(puzzle.permutation_parity(), Parity::Even);
+
+        // On the 3x3x3, a quarter turn cycles four corners and four edges.
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = Puzzle::new(ty);
+        apply(&mut puzzle, "U");
+        assert_eq!(puzzle.permutation_parity(), Parity::Even);
+    }
+
+    #[test]
+    fn test_parity_constraint() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 2 },
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks3D { layer_count: 4 },
+            PuzzleTypeEnum::Rubiks3D { layer_count: 5 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 2 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let solved = Puzzle::new(ty);
+            let every_twist_is_even = itertools::iproduct!(
+                (0..solved.twist_axes().len() as _).map(TwistAxis),
+                (0..solved.twist_directions().len() as _).map(TwistDirection),
+                0..solved.layer_count()
+            )
+            .all(|(axis, direction, layer)| {
+                let mut puzzle = solved.clone();
+                let layers = LayerMask(1 << layer);
+                puzzle
+                    .twist(Twist {
+                        axis,
+                        direction,
+                        layers,
+                    })
+                    .is_err()
+                    || puzzle.permutation_parity() == Parity::Even
+            });
+            assert_eq!(ty.is_parity_constrained(), every_twist_is_even, "{ty}");
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use cgmath::{One, Quaternion, Rotation};
 use enum_iterator::Sequence;
 use itertools::Itertools;
-use rand::Rng;
-use regex::Regex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use std::fmt;
@@ -27,6 +27,14 @@ pub trait PuzzleType {
     /// Returns the maximum radius of the puzzle's 3D projection.
     fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32;
     fn scramble_moves_count(&self) -> usize;
+    /// Returns a list of well-known patterns that can be made on the puzzle.
+    fn named_patterns(&self) -> &'static [NamedPattern] {
+        &[]
+    }
+    /// Returns a list of algorithms that each cycle pieces of a single type.
+    fn cycle_algorithms(&self) -> &'static [CycleAlgorithm] {
+        &[]
+    }
 
     fn faces(&self) -> &[FaceInfo];
     fn pieces(&self) -> &[PieceInfo];
@@ -68,6 +76,14 @@ pub trait PuzzleType {
 
     fn make_recenter_twist(&self, axis: TwistAxis) -> Result<Twist, String>;
 
+    /// Returns an error if the twist axis or direction does not exist on this
+    /// puzzle.
+    fn check_twist(&self, twist: Twist) -> Result<(), IndexOutOfRange> {
+        self.try_info(twist.axis)?;
+        self.try_info(twist.direction)?;
+        Ok(())
+    }
+
     fn reverse_twist(&self, twist: Twist) -> Twist {
         Twist {
             axis: twist.axis,
@@ -81,23 +97,26 @@ pub trait PuzzleType {
     fn chain_twist_directions(&self, dirs: &[TwistDirection]) -> Option<TwistDirection>;
 
     fn notation_scheme(&self) -> &NotationScheme;
+    fn notation_suggestions(&self, partial: &str) -> Vec<String> {
+        self.notation_scheme().suggestions(partial)
+    }
     fn split_twists_string<'s>(&self, string: &'s str) -> regex::Matches<'static, 's> {
-        const TWIST_PATTERN: &str = r"(\{[\d\s,]*\}|[^\s()])+";
-        // one or more of either      (                    )+
-        //     a pair of `{}`          \{        \}
-        //       containing digits,      [\d   ]*
-        //                  whitespace,     \s
-        //                  and commas        ,
-        //   or                                    |
-        //     any symbol other than                [^    ]
-        //       whitespace                           \s
-        //       and parens                             ()
-
-        lazy_static! {
-            static ref TWIST_REGEX: Regex = Regex::new(TWIST_PATTERN).unwrap();
+        notation::split_twists(string)
+    }
+
+    /// Parses a string of twists, skipping any that are invalid. Returns the
+    /// valid twists along with the byte range and error message of each
+    /// invalid one.
+    fn parse_twists(&self, string: &str) -> (Vec<Twist>, Vec<(Range<usize>, String)>) {
+        let mut twists = vec![];
+        let mut errors = vec![];
+        for m in self.split_twists_string(string) {
+            match self.notation_scheme().parse_twist(m.as_str()) {
+                Ok(twist) => twists.push(twist),
+                Err(e) => errors.push((m.range(), e)),
+            }
         }
-
-        TWIST_REGEX.find_iter(string)
+        (twists, errors)
     }
 
     fn twist_command_short_description(
@@ -179,6 +198,10 @@ pub trait PuzzleState: PuzzleType {
 
     fn is_solved(&self) -> bool;
 
+    /// Returns the face that a sticker is currently on, along with the
+    /// current location of its piece as a layer index along each axis.
+    fn sticker_location(&self, sticker: Sticker) -> (Face, SmallVec<[u8; 4]>);
+
     #[cfg(debug_assertions)]
     fn sticker_debug_info(&self, _s: &mut String, _sticker: Sticker) {}
 }
@@ -231,6 +254,23 @@ impl PuzzleTypeEnum {
             PuzzleTypeEnum::Rubiks4D { .. } => true,
         }
     }
+    /// Returns whether every twist is an even permutation of the pieces, in
+    /// which case a state with odd permutation parity can't be solved.
+    pub fn is_parity_constrained(&self) -> bool {
+        match *self {
+            // On cubes with an even number of layers, an inner slice twist
+            // cycles an odd number of groups of four pieces. (On the 2x2x2,
+            // so does an outer layer twist.)
+            PuzzleTypeEnum::Rubiks3D { layer_count } => layer_count % 2 == 1,
+            // On hypercubes, every rotation of a layer cycles an even number
+            // of groups of pieces, regardless of the number of layers.
+            PuzzleTypeEnum::Rubiks4D { .. } => true,
+        }
+    }
+    /// Returns whether `Puzzle::random_state()` supports this puzzle type.
+    pub fn supports_random_state(&self) -> bool {
+        matches!(*self, PuzzleTypeEnum::Rubiks3D { layer_count: 2 })
+    }
 }
 impl Default for PuzzleTypeEnum {
     fn default() -> Self {
@@ -282,8 +322,7 @@ impl FromStr for Twist {
     }
 }
 impl Twist {
-    pub fn from_rng(ty: PuzzleTypeEnum) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn from_rng(ty: PuzzleTypeEnum, rng: &mut impl Rng) -> Self {
         Self {
             axis: TwistAxis(rng.gen_range(0..ty.twist_axes().len()) as _),
             direction: TwistDirection(rng.gen_range(0..ty.twist_directions().len()) as _),
@@ -296,6 +335,16 @@ impl Twist {
     }
 }
 
+/// Returns a deterministic RNG for generating scrambles, so that the same seed
+/// always produces the same scramble. Use `rand::thread_rng()` instead for
+/// anything that must be unpredictable.
+///
+/// The sequence for a given seed may change when the `rand` crate is upgraded,
+/// so seeds are only useful for reproducing scrambles within one version.
+pub fn scramble_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
 /// Puzzle of any type.
 #[enum_dispatch(PuzzleType, PuzzleState)]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -322,6 +371,17 @@ impl Puzzle {
             }
         }
     }
+    /// Creates a puzzle in a uniformly random state, or returns `None` if
+    /// random states aren't supported for the puzzle type. See
+    /// `PuzzleTypeEnum::supports_random_state()`.
+    pub fn random_state(ty: PuzzleTypeEnum, rng: &mut impl Rng) -> Option<Puzzle> {
+        match ty {
+            PuzzleTypeEnum::Rubiks3D { layer_count } => {
+                Rubiks3D::random_state(layer_count, rng).map(Puzzle::Rubiks3D)
+            }
+            PuzzleTypeEnum::Rubiks4D { .. } => None,
+        }
+    }
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
@@ -341,6 +401,9 @@ pub trait PuzzleInfo<T> {
     type Output;
 
     fn info(&self, thing: T) -> &Self::Output;
+    /// Same as `info()`, but returns an error instead of panicking if the
+    /// index is out of range.
+    fn try_info(&self, thing: T) -> Result<&Self::Output, IndexOutOfRange>;
 }
 macro_rules! impl_puzzle_info_trait {
     (fn $method:ident($thing:ty) -> &$thing_info:ty) => {
@@ -350,6 +413,14 @@ macro_rules! impl_puzzle_info_trait {
             fn info(&self, thing: $thing) -> &$thing_info {
                 &self.$method()[thing.0 as usize]
             }
+            fn try_info(&self, thing: $thing) -> Result<&$thing_info, IndexOutOfRange> {
+                let list = self.$method();
+                list.get(thing.0 as usize).ok_or(IndexOutOfRange {
+                    kind: stringify!($thing),
+                    index: thing.0 as usize,
+                    len: list.len(),
+                })
+            }
         }
     };
 }
@@ -360,6 +431,27 @@ impl_puzzle_info_trait!(fn twist_axes(TwistAxis) -> &TwistAxisInfo);
 impl_puzzle_info_trait!(fn twist_directions(TwistDirection) -> &TwistDirectionInfo);
 impl_puzzle_info_trait!(fn piece_types(PieceType) -> &PieceTypeInfo);
 
+/// Error returned when indexing puzzle info with an out-of-range index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IndexOutOfRange {
+    /// Kind of thing being indexed (e.g., `"Piece"`).
+    pub kind: &'static str,
+    /// Index that was requested.
+    pub index: usize,
+    /// Number of things of that kind.
+    pub len: usize,
+}
+impl fmt::Display for IndexOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} index {} out of range (length {})",
+            self.kind, self.index, self.len,
+        )
+    }
+}
+impl std::error::Error for IndexOutOfRange {}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PieceInfo {
     pub stickers: SmallVec<[Sticker; 8]>,
@@ -381,6 +473,23 @@ impl FaceInfo {
     }
 }
 
+/// Well-known pattern that can be made by applying a sequence of twists to a
+/// solved puzzle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NamedPattern {
+    pub name: &'static str,   // e.g., "Checkerboard"
+    pub twists: &'static str, // e.g., "M2 E2 S2"
+}
+
+/// Sequence of twists that moves only pieces of one type, leaving all other
+/// pieces solved. Centers have an orientation too, so the net turn of every
+/// face must be a multiple of a full rotation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CycleAlgorithm {
+    pub piece_type: &'static str, // e.g., "edge"
+    pub twists: &'static str,     // e.g., "R2 U2 R2 F2 R2 U2 R2 F2"
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TwistAxisInfo {
     pub name: &'static str, // e.g., "R"
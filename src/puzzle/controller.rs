@@ -6,10 +6,13 @@ use bitvec::slice::BitSlice;
 use bitvec::vec::BitVec;
 use cgmath::{Deg, InnerSpace, One, Quaternion, Rotation, Rotation3};
 use instant::Duration;
+use itertools::Itertools;
 use num_enum::FromPrimitive;
+use rand::Rng;
 use std::borrow::Cow;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::ops::{BitOr, BitOrAssign};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 /// If at least this much of a twist is animated in one frame, just skip the
@@ -73,6 +76,8 @@ pub struct PuzzleController {
     scramble_state: ScrambleState,
     /// Scramble twists.
     scramble: Vec<Twist>,
+    /// Seed used to generate the scramble, if it was a seeded full scramble.
+    scramble_seed: Option<u64>,
     /// Undo history.
     undo_buffer: Vec<HistoryEntry>,
     /// Redo history.
@@ -102,9 +107,17 @@ pub struct PuzzleController {
     /// represented as `f32` for animation.
     visual_piece_states: Vec<VisualPieceState>,
 
+    /// Text label to draw on each sticker, such as for tutorials.
+    sticker_labels: Vec<Option<String>>,
+
     /// Cached sticker geometry.
     cached_geometry: Option<Arc<Vec<ProjectedStickerGeometry>>>,
     cached_geometry_params: Option<StickerGeometryParams>,
+
+    /// Log file fields not recognized by this version of Hyperspeedcube,
+    /// which are kept so that they are written back when the log file is
+    /// saved.
+    unknown_log_fields: BTreeMap<String, serde_yaml::Value>,
 }
 impl Default for PuzzleController {
     fn default() -> Self {
@@ -137,6 +150,7 @@ impl PuzzleController {
 
             scramble_state: ScrambleState::None,
             scramble: vec![],
+            scramble_seed: None,
             undo_buffer: vec![],
             redo_buffer: vec![],
 
@@ -152,21 +166,95 @@ impl PuzzleController {
 
             visual_piece_states: vec![VisualPieceState::default(); ty.pieces().len()],
 
+            sticker_labels: vec![None; ty.stickers().len()],
+
             cached_geometry: None,
             cached_geometry_params: None,
+
+            unknown_log_fields: BTreeMap::new(),
         }
     }
+    /// Returns log file fields that were not recognized when loading the
+    /// puzzle.
+    pub fn unknown_log_fields(&self) -> &BTreeMap<String, serde_yaml::Value> {
+        &self.unknown_log_fields
+    }
+    /// Sets log file fields that were not recognized when loading the puzzle.
+    pub fn set_unknown_log_fields(&mut self, fields: BTreeMap<String, serde_yaml::Value>) {
+        self.unknown_log_fields = fields;
+    }
     /// Resets the puzzle.
     pub fn reset(&mut self) {
         *self = Self::new(self.ty());
     }
 
+    /// Returns a rough estimate of the number of bytes of memory used by the
+    /// puzzle, including twist history, queued animations, and cached sticker
+    /// geometry. Puzzle descriptions are shared and never freed, so they
+    /// aren't counted.
+    pub fn estimated_memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+
+        let twists = self.scramble.len() * size_of::<Twist>()
+            + (self.undo_buffer.len() + self.redo_buffer.len()) * size_of::<HistoryEntry>();
+        let animations = self
+            .twist_anim
+            .queue
+            .iter()
+            .map(|anim| size_of::<TwistAnimation>() + anim.state.estimated_memory_bytes())
+            .sum::<usize>();
+        let pieces = self.visual_piece_states.len() * size_of::<VisualPieceState>()
+            + self.visible_pieces.capacity() / 8;
+        let labels = self
+            .sticker_labels
+            .iter()
+            .map(|label| size_of::<Option<String>>() + label.as_ref().map_or(0, |s| s.capacity()))
+            .sum::<usize>();
+        let mesh = self
+            .cached_geometry
+            .iter()
+            .flat_map(|geometry| geometry.iter())
+            .map(|geometry| {
+                size_of::<ProjectedStickerGeometry>()
+                    + geometry.verts.len() * size_of::<cgmath::Point3<f32>>()
+                    + (geometry.front_polygons.len() + geometry.back_polygons.len())
+                        * size_of::<Polygon>()
+            })
+            .sum::<usize>();
+
+        size_of::<Self>()
+            + self.puzzle.estimated_memory_bytes()
+            + twists
+            + animations
+            + pieces
+            + labels
+            + mesh
+    }
+
     /// Returns whether the puzzle has been scrambled, solved, etc..
     pub fn scramble_state(&self) -> ScrambleState {
         self.scramble_state
     }
     /// Reset and then scramble some number of moves.
     pub fn scramble_n(&mut self, n: usize) -> Result<(), &'static str> {
+        self.scramble_n_with_progress(
+            n,
+            &mut rand::thread_rng(),
+            &AtomicBool::new(false),
+            |_, _| (),
+        )
+    }
+    /// Reset and then scramble some number of moves using `rng`, calling
+    /// `progress` with the number of moves done so far and the total after each
+    /// move. If `cancel` becomes `true`, the puzzle is reset and an error is
+    /// returned.
+    pub fn scramble_n_with_progress(
+        &mut self,
+        n: usize,
+        rng: &mut impl Rng,
+        cancel: &AtomicBool,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<(), &'static str> {
         self.reset();
 
         // Set a reasonable limit on the number of moves.
@@ -177,17 +265,181 @@ impl PuzzleController {
 
         // Use a `while` loop instead of a `for` loop because moves may cancel.
         while self.undo_buffer.len() < n {
-            self.twist(Twist::from_rng(self.ty()))?;
+            if cancel.load(Ordering::Relaxed) {
+                self.reset();
+                return Err("Scramble cancelled");
+            }
+            self.twist(Twist::from_rng(self.ty(), rng))?;
+            progress(self.undo_buffer.len(), n);
         }
         self.add_scramble_marker(ScrambleState::Partial);
         Ok(())
     }
-    /// Scramble the puzzle completely.
-    pub fn scramble_full(&mut self) -> Result<(), &'static str> {
-        self.scramble_n(self.scramble_moves_count())?;
+    /// Scramble the puzzle completely using a random seed, and return the seed
+    /// so that the scramble can be reproduced.
+    pub fn scramble_full(&mut self) -> Result<u64, &'static str> {
+        let seed = rand::random();
+        self.scramble_full_seeded(seed)?;
+        Ok(seed)
+    }
+    /// Scramble the puzzle completely and reproducibly. The same seed always
+    /// gives the same scramble; see `scramble_rng()`.
+    pub fn scramble_full_seeded(&mut self, seed: u64) -> Result<(), &'static str> {
+        let n = self.scramble_moves_count();
+        let mut rng = scramble_rng(seed);
+        self.scramble_n_with_progress(n, &mut rng, &AtomicBool::new(false), |_, _| ())?;
         self.scramble_state = ScrambleState::Full;
+        self.scramble_seed = Some(seed);
+        Ok(())
+    }
+    /// Returns the seed used to generate the scramble, if the puzzle was
+    /// scrambled using `scramble_full_seeded()`.
+    pub fn scramble_seed(&self) -> Option<u64> {
+        self.scramble_seed
+    }
+    /// Sets the seed used to generate the scramble, such as when loading a log
+    /// file.
+    pub fn set_scramble_seed(&mut self, seed: Option<u64>) {
+        self.scramble_seed = seed;
+    }
+    /// Returns a scramble that takes a solved puzzle to a uniformly random
+    /// state, using the solver to find a sequence of twists that reaches it.
+    /// This can take a few seconds, so it doesn't touch any puzzle and can be
+    /// called from a worker thread. `cancel` and `progress` work like in
+    /// `scramble_n_with_progress()`. Use `apply_scramble()` to apply the
+    /// result.
+    pub fn generate_random_state_scramble(
+        ty: PuzzleTypeEnum,
+        seed: u64,
+        cancel: &AtomicBool,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<Vec<Twist>, &'static str> {
+        /// Depth of the pruning table used to find the scramble.
+        const PRUNING_DEPTH: usize = 4;
+
+        if !ty.supports_random_state() {
+            return Err("Random-state scrambles aren't supported for this puzzle");
+        }
+        let pruning_table = solve::PruningTable::get(ty, PRUNING_DEPTH);
+        let budget = solve::SolveBudget {
+            max_nodes: 10_000_000,
+            max_time: Some(Duration::from_secs(10)),
+        };
+        let mut rng = scramble_rng(seed);
+        let (_target, scramble) = solve::random_state_scramble(
+            ty,
+            &mut rng,
+            budget,
+            Some(&pruning_table),
+            cancel,
+            progress,
+        )?;
+        Ok(scramble)
+    }
+    /// Reset and then apply a scramble, such as one from
+    /// `generate_random_state_scramble()`.
+    pub fn apply_scramble(
+        &mut self,
+        scramble: &[Twist],
+        scramble_state: ScrambleState,
+    ) -> Result<(), &'static str> {
+        self.reset();
+        for &twist in scramble {
+            self.twist(twist)?;
+        }
+        self.add_scramble_marker(scramble_state);
+        Ok(())
+    }
+    /// Reset and then scramble only pieces of one type, leaving all others
+    /// solved.
+    pub fn scramble_piece_type(&mut self, piece_type: &str) -> Result<(), String> {
+        /// Number of random conjugates of the cycle algorithm to apply.
+        const CYCLE_COUNT: usize = 10;
+        /// Number of random twists in each conjugate's setup.
+        const SETUP_LEN: usize = 3;
+
+        self.reset();
+
+        let algorithm = self
+            .cycle_algorithms()
+            .iter()
+            .find(|alg| alg.piece_type == piece_type)
+            .ok_or_else(|| format!("cannot scramble only {piece_type} pieces"))?;
+        let (cycle, errors) = self.parse_twists(algorithm.twists);
+        if let Some((_range, e)) = errors.into_iter().next() {
+            return Err(e);
+        }
+
+        // Every twist sends pieces of each type to pieces of the same type,
+        // so conjugating the cycle by random twists still only affects pieces
+        // of that type.
+        let mut rng = rand::thread_rng();
+        for _ in 0..CYCLE_COUNT {
+            let setup = (0..SETUP_LEN)
+                .map(|_| Twist::from_rng(self.ty(), &mut rng))
+                .collect_vec();
+            let undo_setup = setup
+                .iter()
+                .rev()
+                .map(|&t| self.reverse_twist(t))
+                .collect_vec();
+            for twist in setup
+                .into_iter()
+                .chain(cycle.iter().copied())
+                .chain(undo_setup)
+            {
+                self.twist(twist)?;
+            }
+        }
+        self.add_scramble_marker(ScrambleState::Partial);
+        Ok(())
+    }
+    /// Resets the puzzle and applies a named pattern to it as a scramble.
+    pub fn apply_pattern(&mut self, pattern: &NamedPattern) -> Result<(), String> {
+        self.reset();
+
+        let (twists, errors) = self.parse_twists(pattern.twists);
+        if let Some((_range, e)) = errors.into_iter().next() {
+            return Err(e);
+        }
+        for twist in twists {
+            self.twist(twist)?;
+        }
+        self.add_scramble_marker(ScrambleState::Partial);
         Ok(())
     }
+    /// Returns the set of pieces that have a sticker on the wrong face. See
+    /// `Puzzle::unsolved_pieces()`.
+    pub fn unsolved_pieces(&self) -> BitVec {
+        self.puzzle.unsolved_pieces()
+    }
+    /// Returns the named pattern that the puzzle is currently in, ignoring the
+    /// orientation of the whole puzzle.
+    pub fn current_pattern(&self) -> Option<&'static NamedPattern> {
+        let patterns = self.named_patterns();
+        if patterns.is_empty() {
+            return None;
+        }
+
+        let signature = self.puzzle.color_signature();
+        patterns.iter().find(|pattern| {
+            let (twists, errors) = self.parse_twists(pattern.twists);
+            let mut pattern_state = Puzzle::new(self.ty());
+            errors.is_empty()
+                && twists.into_iter().all(|t| pattern_state.twist(t).is_ok())
+                && pattern_state.color_signature() == signature
+        })
+    }
+    /// Returns a rough measure of how well-scrambled the puzzle is, from 0.0
+    /// to 1.0. See `Puzzle::scramble_quality()`.
+    pub fn scramble_quality(&self) -> f32 {
+        self.puzzle.scramble_quality()
+    }
+    /// Returns whether the puzzle is in a state that can't be solved because
+    /// it has the wrong permutation parity.
+    pub fn has_unsolvable_parity(&self) -> bool {
+        self.ty().is_parity_constrained() && self.puzzle.permutation_parity() == Parity::Odd
+    }
     /// Marks the puzzle as scrambled.
     pub fn add_scramble_marker(&mut self, new_scramble_state: ScrambleState) {
         self.skip_twist_animations();
@@ -224,6 +476,10 @@ impl PuzzleController {
         if twist.layers == LayerMask(0) {
             return Err("invalid layer mask");
         }
+        if let Err(e) = self.check_twist(twist) {
+            log::warn!("Invalid twist {twist:?}: {e}");
+            return Err("invalid twist");
+        }
 
         self.mark_unsaved();
         self.redo_buffer.clear();
@@ -690,6 +946,16 @@ impl PuzzleController {
         self.visible_pieces = visible_pieces.to_bitvec();
         self.visible_pieces.resize(self.pieces().len(), false);
     }
+
+    /// Returns the text label to draw on each sticker.
+    pub fn sticker_labels(&self) -> &[Option<String>] {
+        &self.sticker_labels
+    }
+    /// Sets the text label to draw on each sticker.
+    pub fn set_sticker_labels(&mut self, mut labels: Vec<Option<String>>) {
+        labels.resize(self.stickers().len(), None);
+        self.sticker_labels = labels;
+    }
     /// Sets the set of non-hidden pieces.
     pub fn set_visible_pieces_preview(
         &mut self,
@@ -1172,3 +1438,163 @@ impl VisualPieceState {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        let n = ty.scramble_moves_count();
+
+        let cancel = AtomicBool::new(false);
+        let mut progress_calls = 0;
+        let mut rng = rand::thread_rng();
+        let result = puzzle.scramble_n_with_progress(n, &mut rng, &cancel, |done, total| {
+            assert_eq!(total, n);
+            progress_calls += 1;
+            if done >= 5 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        });
+
+        assert_eq!(result, Err("Scramble cancelled"));
+        assert!(progress_calls >= 5 && progress_calls < n);
+        assert_eq!(puzzle.scramble_state(), ScrambleState::None);
+        assert!(!puzzle.has_undo());
+    }
+
+    #[test]
+    fn test_seeded_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+
+        // The scramble RNG reproduces the same sequence from the same seed.
+        let twists = |seed| {
+            let mut rng = scramble_rng(seed);
+            (0..20).map(|_| Twist::from_rng(ty, &mut rng)).collect_vec()
+        };
+        assert_eq!(twists(42), twists(42));
+        assert_ne!(twists(42), twists(43));
+
+        let scrambled = |seed| {
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle.scramble_full_seeded(seed).unwrap();
+            assert_eq!(puzzle.scramble_state(), ScrambleState::Full);
+            puzzle.puzzle
+        };
+        assert_eq!(scrambled(7), scrambled(7));
+        assert_ne!(scrambled(7), scrambled(8));
+    }
+
+    #[test]
+    fn test_estimated_memory_bytes() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        let solved_bytes = puzzle.estimated_memory_bytes();
+        puzzle.scramble_n(100).unwrap();
+        // The scramble takes up memory, but the puzzle description is shared.
+        assert!(puzzle.estimated_memory_bytes() > solved_bytes);
+        assert!(puzzle.estimated_memory_bytes() < solved_bytes + 100 * 1024);
+    }
+
+    #[test]
+    fn test_apply_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let mut puzzle = PuzzleController::new(ty);
+        let (scramble, _errors) = puzzle.parse_twists("R U F' R2");
+
+        puzzle.scramble_n(5).unwrap();
+        puzzle
+            .apply_scramble(&scramble, ScrambleState::Full)
+            .unwrap();
+        assert_eq!(puzzle.scramble(), &scramble[..]);
+        assert_eq!(puzzle.scramble_state(), ScrambleState::Full);
+        assert!(!puzzle.has_undo());
+
+        let unsupported = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let no_cancel = AtomicBool::new(false);
+        assert!(PuzzleController::generate_random_state_scramble(
+            unsupported,
+            0,
+            &no_cancel,
+            |_, _| ()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_unsolved_pieces() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        assert!(puzzle.unsolved_pieces().not_any());
+
+        let twist = Twist {
+            axis: TwistAxis(0),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        };
+        let moved_pieces = Puzzle::new(ty).pieces_affected_by_twist(twist);
+        puzzle.twist(twist).unwrap();
+        // Pieces that only turn in place, such as the center of the twisted
+        // face, still look solved.
+        let unsolved = puzzle.unsolved_pieces();
+        assert!(unsolved.any());
+        assert!(unsolved.count_ones() < moved_pieces.len());
+        for piece in unsolved.iter_ones() {
+            assert!(moved_pieces.contains(&Piece(piece as _)));
+        }
+    }
+
+    #[test]
+    fn test_current_pattern() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let mut puzzle = PuzzleController::new(ty);
+        assert_eq!(puzzle.current_pattern(), None);
+
+        let checkerboard = puzzle.named_patterns()[0];
+        puzzle.apply_pattern(&checkerboard).unwrap();
+        assert_eq!(puzzle.current_pattern(), Some(&checkerboard));
+
+        // Rotating the whole puzzle doesn't change the pattern.
+        let (rotations, _errors) = puzzle.parse_twists("x y");
+        for twist in rotations {
+            puzzle.twist(twist).unwrap();
+        }
+        assert_eq!(puzzle.current_pattern(), Some(&checkerboard));
+
+        let (twists, _errors) = puzzle.parse_twists("R");
+        puzzle.twist(twists[0]).unwrap();
+        assert_eq!(puzzle.current_pattern(), None);
+    }
+
+    #[test]
+    fn test_scramble_piece_type() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 3 };
+        let piece_type = |name: &str| {
+            PieceType(
+                ty.piece_types()
+                    .iter()
+                    .position(|p| p.name == name)
+                    .unwrap() as _,
+            )
+        };
+
+        // Centers have an orientation too, so they must not be twisted either.
+        for name in ["corner", "edge"] {
+            let mut puzzle = PuzzleController::new(ty);
+            puzzle.scramble_piece_type(name).unwrap();
+
+            let diff = Puzzle::new(ty).diff(&puzzle.puzzle).unwrap();
+            assert!(diff.any());
+            for piece in (0..ty.pieces().len() as _).map(Piece) {
+                if ty.info(piece).piece_type != piece_type(name) {
+                    assert!(!diff[piece.0 as usize], "{piece:?} was scrambled");
+                }
+            }
+        }
+
+        assert!(puzzle.scramble_piece_type("wing").is_err());
+    }
+}
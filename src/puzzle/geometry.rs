@@ -31,6 +31,9 @@ pub struct StickerGeometryParams {
     pub face_scale: f32,
     /// `(sticker width) / (puzzle diameter)`. Ranges from 0.0 to 1.0.
     pub sticker_scale: f32,
+    /// `(piece offset from its normal position) / (piece distance from
+    /// center)`. Ranges from 0.0 to infinity.
+    pub piece_explode: f32,
 
     /// 4D FOV, in degrees.
     pub fov_4d: f32,
@@ -103,6 +106,7 @@ impl StickerGeometryParams {
             sticker_grid_scale,
             face_scale,
             sticker_scale,
+            piece_explode: view_prefs.piece_explode,
 
             fov_4d: view_prefs.fov_4d,
             fov_3d: view_prefs.fov_3d,
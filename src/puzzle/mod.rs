@@ -3,12 +3,15 @@
 #[macro_use]
 mod common;
 
+mod analysis;
 pub mod controller;
 pub mod geometry;
 pub mod notation;
 pub mod rubiks_3d;
 pub mod rubiks_4d;
+pub mod solve;
 
+pub use analysis::Parity;
 pub use common::*;
 pub use controller::*;
 pub use geometry::*;
@@ -22,6 +25,7 @@ pub mod traits {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
     use std::collections::HashSet;
 
     use super::*;
@@ -92,6 +96,104 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_tokenize_spanned() {
+        use TokenKind::*;
+
+        let p = Rubiks3D::new(3);
+        assert_eq!(
+            p.notation_scheme().tokenize_spanned("(R U')2 {1, 3}Fw2 Q"),
+            vec![
+                (0..1, Grouping),
+                (1..2, Axis),
+                (3..4, Axis),
+                (4..5, Direction),
+                (5..6, Grouping),
+                // The parser doesn't support repetition.
+                (6..7, Invalid),
+                (8..14, Layers),
+                (14..16, Axis),
+                (16..17, Direction),
+                (18..19, Invalid),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_twists_recovers_from_errors() {
+        let p = Rubiks3D::new(3);
+        let notation = p.notation_scheme();
+
+        let (twists, errors) = p.parse_twists("R Q U' X2 F");
+        assert_eq!(
+            twists,
+            ["R", "U'", "F"].map(|s| notation.parse_twist(s).unwrap()),
+        );
+        assert_eq!(
+            errors.iter().map(|(range, _)| range.clone()).collect_vec(),
+            [2..3, 7..9],
+        );
+    }
+
+    #[test]
+    fn test_reverse_twist() {
+        for ty in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 3 },
+        ] {
+            let solved = Puzzle::new(ty);
+            for twist in iter_all_twists(&ty) {
+                let reversed = ty.reverse_twist(twist);
+                assert_eq!(ty.reverse_twist(reversed), twist);
+
+                let mut p = solved.clone();
+                p.twist(twist).unwrap();
+                p.twist(reversed).unwrap();
+                assert_eq!(p, solved, "{twist:?} then {reversed:?} is not identity");
+            }
+        }
+    }
+
+    #[test]
+    fn test_notation_suggestions() {
+        let p = Rubiks3D::new(3);
+
+        let suggestions = p.notation_suggestions("R");
+        for s in ["R", "R'", "R2", "Rw", "Rw'"] {
+            assert!(suggestions.contains(&s.to_string()), "missing {s:?}");
+        }
+        assert!(suggestions.iter().all(|s| s.starts_with('R')));
+
+        assert!(p.notation_suggestions("2R").contains(&"2R'".to_string()));
+        assert!(p.notation_suggestions("M").contains(&"M2".to_string()));
+        assert!(p.notation_suggestions("Q").is_empty());
+    }
+
+    #[test]
+    fn test_try_info_out_of_range() {
+        let p = Rubiks3D::new(3);
+        assert_eq!(p.try_info(Piece(0)), Ok(p.info(Piece(0))));
+
+        let err = p.try_info(Piece(1000)).unwrap_err();
+        assert_eq!(
+            err,
+            IndexOutOfRange {
+                kind: "Piece",
+                index: 1000,
+                len: 26,
+            },
+        );
+        assert_eq!(err.to_string(), "Piece index 1000 out of range (length 26)");
+
+        let bad_twist = Twist {
+            axis: TwistAxis(200),
+            ..Default::default()
+        };
+        assert!(p.check_twist(bad_twist).is_err());
+        let mut controller = PuzzleController::new(p.ty());
+        assert_eq!(controller.twist(bad_twist), Err("invalid twist"));
+    }
+
     fn iter_all_twists(p: &impl PuzzleType) -> impl Iterator<Item = Twist> {
         itertools::iproduct!(
             (0..p.twist_axes().len() as _).map(TwistAxis),
@@ -1,6 +1,7 @@
 use itertools::Itertools;
 use regex::Regex;
 use std::fmt;
+use std::ops::Range;
 
 use super::*;
 
@@ -13,6 +14,21 @@ pub struct NotationScheme {
     // TODO: flag to allow chaining directions (e.g., "Rxyx'y")
 }
 
+/// Kind of token in a twist string, for syntax highlighting.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Parenthesis, which is ignored by the parser.
+    Grouping,
+    /// Layer mask prefix, such as `2` or `{1,3}`.
+    Layers,
+    /// Twist axis, including any block suffix or alias.
+    Axis,
+    /// Twist direction.
+    Direction,
+    /// Anything that could not be parsed.
+    Invalid,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub(super) enum Alias {
     AxisLayers(TwistAxis, LayerMask),
@@ -167,6 +183,116 @@ impl NotationScheme {
         }
     }
 
+    /// Splits a string of twists into tokens the same way as
+    /// `PuzzleType::parse_twists()`, returning the byte range and kind of each
+    /// token.
+    pub fn tokenize_spanned(&self, s: &str) -> Vec<(Range<usize>, TokenKind)> {
+        let mut tokens = vec![];
+        let push_groupings = |tokens: &mut Vec<_>, range: Range<usize>| {
+            // Between twists there is only whitespace and parentheses.
+            for (i, c) in s[range.clone()].char_indices() {
+                if c == '(' || c == ')' {
+                    let start = range.start + i;
+                    tokens.push((start..start + 1, TokenKind::Grouping));
+                }
+            }
+        };
+
+        let mut prev_end = 0;
+        for m in split_twists(s) {
+            push_groupings(&mut tokens, prev_end..m.start());
+            if self.parse_twist(m.as_str()).is_ok() {
+                self.tokenize_twist(m.as_str(), m.start(), &mut tokens);
+            } else {
+                tokens.push((m.range(), TokenKind::Invalid));
+            }
+            prev_end = m.end();
+        }
+        push_groupings(&mut tokens, prev_end..s.len());
+
+        tokens
+    }
+    /// Splits a valid twist into tokens.
+    fn tokenize_twist(&self, s: &str, offset: usize, tokens: &mut Vec<(Range<usize>, TokenKind)>) {
+        let span =
+            |start: &str, end: &str| offset + s.len() - start.len()..offset + s.len() - end.len();
+
+        // Check for aliases.
+        let matching_alias = strip_any_prefix(
+            s,
+            self.aliases
+                .iter()
+                .map(|(alias_str, alias)| (alias, alias_str)),
+        );
+        let remaining = if let Some((_, remaining)) = matching_alias {
+            tokens.push((span(s, remaining), TokenKind::Axis));
+            remaining
+        } else {
+            let (layers, remaining) = self.strip_layer_mask_prefix(s).unwrap_or((None, s));
+            if layers.is_some() {
+                tokens.push((span(s, remaining), TokenKind::Layers));
+            }
+            let mut after_axis =
+                match strip_any_prefix(remaining, self.axis_names.iter().enumerate()) {
+                    Some((_, after_axis)) => after_axis,
+                    None => return,
+                };
+            if let Some(block_suffix) = &self.block_suffix {
+                after_axis = after_axis.strip_prefix(block_suffix).unwrap_or(after_axis);
+            }
+            tokens.push((span(remaining, after_axis), TokenKind::Axis));
+            after_axis
+        };
+
+        if !remaining.is_empty() {
+            tokens.push((span(remaining, ""), TokenKind::Direction));
+        }
+    }
+
+    /// Returns every valid twist string that starts with `partial`, for
+    /// suggesting completions while the user is typing.
+    pub fn suggestions(&self, partial: &str) -> Vec<String> {
+        let (layers, remaining) = self
+            .strip_layer_mask_prefix(partial)
+            .unwrap_or((None, partial));
+        let layer_prefix = &partial[..partial.len() - remaining.len()];
+
+        let mut bases = vec![];
+        for (i, axis_name) in self.axis_names.iter().enumerate() {
+            let axis = TwistAxis(i as _);
+            bases.push((axis_name.clone(), Some(axis)));
+            if let Some(block_suffix) = &self.block_suffix {
+                bases.push((format!("{axis_name}{block_suffix}"), Some(axis)));
+            }
+        }
+        // Aliases already include a layer mask.
+        if layers.is_none() {
+            for (alias_str, alias) in &self.aliases {
+                match alias {
+                    Alias::AxisLayers(axis, _) => bases.push((alias_str.clone(), Some(*axis))),
+                    Alias::EntireTwist(_) => bases.push((alias_str.clone(), None)),
+                }
+            }
+        }
+
+        bases
+            .into_iter()
+            .flat_map(|(base, axis)| match axis {
+                Some(axis) => self
+                    .direction_names
+                    .iter()
+                    .map(|dir| format!("{base}{}", dir.for_axis(axis)))
+                    .collect_vec(),
+                None => vec![base],
+            })
+            .filter(|s| s.starts_with(remaining))
+            .map(|s| format!("{layer_prefix}{s}"))
+            .filter(|s| self.parse_twist(s).is_ok())
+            .sorted_by_key(|s| (s.len(), s.clone()))
+            .dedup()
+            .collect()
+    }
+
     fn parse_twist_direction(
         &self,
         axis: TwistAxis,
@@ -213,6 +339,27 @@ impl NotationScheme {
     }
 }
 
+/// Splits a string of twists into substrings that each contain one twist.
+/// Whitespace and parentheses between twists are ignored.
+pub(super) fn split_twists(string: &str) -> regex::Matches<'static, '_> {
+    const TWIST_PATTERN: &str = r"(\{[\d\s,]*\}|[^\s()])+";
+    // one or more of either      (                    )+
+    //     a pair of `{}`          \{        \}
+    //       containing digits,      [\d   ]*
+    //                  whitespace,     \s
+    //                  and commas        ,
+    //   or                                    |
+    //     any symbol other than                [^    ]
+    //       whitespace                           \s
+    //       and parens                             ()
+
+    lazy_static! {
+        static ref TWIST_REGEX: Regex = Regex::new(TWIST_PATTERN).unwrap();
+    }
+
+    TWIST_REGEX.find_iter(string)
+}
+
 fn strip_any_prefix<'a, 'b, T>(
     s: &'a str,
     possible_prefixes: impl IntoIterator<Item = (T, impl 'b + AsRef<str>)>,
@@ -1,10 +1,13 @@
 //! 3D Rubik's cube.
 
+use bitvec::vec::BitVec;
 use cgmath::*;
 use itertools::Itertools;
 use num_enum::FromPrimitive;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use serde::{de::Error, Deserialize, Deserializer};
-use smallvec::smallvec;
+use smallvec::{smallvec, SmallVec};
 use std::collections::HashMap;
 use std::ops::{Index, IndexMut, RangeInclusive};
 use std::sync::Mutex;
@@ -218,12 +221,42 @@ impl PuzzleType for Rubiks3DDescription {
     fn family_max_layer_count(&self) -> u8 {
         MAX_LAYER_COUNT
     }
-    fn projection_radius_3d(&self, _p: StickerGeometryParams) -> f32 {
-        3.0_f32.sqrt()
+    fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32 {
+        3.0_f32.sqrt() * (1.0 + p.piece_explode)
     }
     fn scramble_moves_count(&self) -> usize {
         10 * self.layer_count as usize // TODO pulled from thin air; probably insufficient for big cubes
     }
+    fn named_patterns(&self) -> &'static [NamedPattern] {
+        match self.layer_count {
+            3 => &[
+                NamedPattern {
+                    name: "Checkerboard",
+                    twists: "M2 E2 S2",
+                },
+                NamedPattern {
+                    name: "Superflip",
+                    twists: "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2",
+                },
+            ],
+            _ => &[],
+        }
+    }
+    fn cycle_algorithms(&self) -> &'static [CycleAlgorithm] {
+        match self.layer_count {
+            3 => &[
+                CycleAlgorithm {
+                    piece_type: "corner",
+                    twists: "R' F R' B2 R F' R' B2 R2",
+                },
+                CycleAlgorithm {
+                    piece_type: "edge",
+                    twists: "R2 U2 R2 F2 R2 U2 R2 F2",
+                },
+            ],
+            _ => &[],
+        }
+    }
 
     fn faces(&self) -> &[FaceInfo] {
         &self.faces
@@ -507,6 +540,12 @@ impl PuzzleState for Rubiks3D {
         }
         true
     }
+
+    fn sticker_location(&self, sticker: Sticker) -> (Face, SmallVec<[u8; 4]>) {
+        let piece = self.info(sticker).piece;
+        let location = self.piece_location(piece).into_iter().collect();
+        (self.sticker_face(sticker).into(), location)
+    }
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "desc")]
@@ -521,6 +560,76 @@ impl Rubiks3D {
         self.desc
     }
 
+    /// Returns the set of pieces whose position or orientation differs
+    /// between two states of the same puzzle.
+    pub fn diff(&self, other: &Self) -> BitVec {
+        std::iter::zip(&*self.piece_states, &*other.piece_states)
+            .map(|(a, b)| a != b)
+            .collect()
+    }
+
+    /// Returns a uniformly random state of the puzzle, or `None` if the puzzle
+    /// isn't a 2x2x2. On a 2x2x2, every permutation of the corners can be
+    /// reached, and the only constraint on their orientations is that the
+    /// total twist is a multiple of a full turn.
+    pub fn random_state(layer_count: u8, rng: &mut impl Rng) -> Option<Self> {
+        if layer_count != 2 {
+            return None;
+        }
+
+        // Find every orientation of a piece by rotating the solved one.
+        let mut orientations = vec![PieceState::default()];
+        let mut i = 0;
+        while let Some(&state) = orientations.get(i) {
+            for (from, to) in itertools::iproduct!(Axis::iter(), Axis::iter()) {
+                let new_state = state.rotate(from, to);
+                if from != to && !orientations.contains(&new_state) {
+                    orientations.push(new_state);
+                }
+            }
+            i += 1;
+        }
+
+        let mut ret = Self::new(layer_count);
+        let mut locations = ret.desc.piece_locations.clone();
+        locations.shuffle(rng);
+        let mut total_twist = 0;
+        for (i, &location) in locations.iter().enumerate() {
+            let piece = Piece(i as _);
+            let is_last = i == locations.len() - 1;
+            // There are three orientations that put the piece in this
+            // location, one for each twist.
+            let candidates = orientations
+                .iter()
+                .copied()
+                .filter(|&state| {
+                    ret[piece] = state;
+                    ret.piece_location(piece) == location
+                        && (!is_last || (total_twist + ret.corner_twist(piece)) % 3 == 0)
+                })
+                .collect_vec();
+            ret[piece] = *candidates.choose(rng)?;
+            total_twist += ret.corner_twist(piece);
+        }
+        Some(ret)
+    }
+    /// Returns the number of clockwise turns that the U or D sticker of a
+    /// corner is away from the U or D face.
+    fn corner_twist(&self, piece: Piece) -> u8 {
+        // Mirrored corners have the opposite clockwise order of faces.
+        let negative_coordinates = self
+            .piece_location(piece)
+            .iter()
+            .filter(|&&x| x == 0)
+            .count();
+        let is_mirrored = negative_coordinates % 2 == 1;
+        match (self[piece][Axis::Y].axis(), is_mirrored) {
+            (Axis::Y, _) => 0,
+            (Axis::X, false) | (Axis::Z, true) => 1,
+            (Axis::Z, false) | (Axis::X, true) => 2,
+        }
+    }
+
     fn piece_location(&self, piece: Piece) -> [u8; 3] {
         let piece_state = self[piece];
         let initial_location = self.desc.piece_locations[piece.0 as usize];
@@ -555,11 +664,14 @@ impl Rubiks3D {
     fn sticker_center_3d(&self, sticker: Sticker, p: StickerGeometryParams) -> Point3<f32> {
         let sticker_info = self.info(sticker);
         let piece = sticker_info.piece;
-        let mut ret = self.piece_center_3d(piece, p);
+        let piece_center = self.piece_center_3d(piece, p);
+        let mut ret = piece_center;
 
         let sticker_face = self.sticker_face(sticker);
         ret[sticker_face.axis() as usize] = sticker_face.sign().float();
-        ret
+
+        // Move the whole piece away from the center.
+        ret + piece_center.to_vec() * p.piece_explode
     }
 
     fn piece_center_coordinate(&self, x: u8, p: StickerGeometryParams) -> f32 {
@@ -929,6 +1041,7 @@ impl Axis {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::preferences::ViewPreferences;
 
     #[test]
     fn test_rubiks_3d_twist_canonicalization() {
@@ -954,6 +1067,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rubiks_3d_piece_explode() {
+        let p = Rubiks3D::new(3);
+        let unexploded = ViewPreferences::default();
+        let exploded = ViewPreferences {
+            piece_explode: 1.0,
+            ..Default::default()
+        };
+        let sticker_center_at = |t| {
+            let view_prefs = unexploded.interpolate(&exploded, t);
+            let params = StickerGeometryParams::new(&view_prefs, p.ty(), None, Quaternion::one());
+            p.sticker_center_3d(Sticker(0), params)
+        };
+
+        let full_offset = sticker_center_at(1.0) - sticker_center_at(0.0);
+        assert!(full_offset.magnitude() > 0.1);
+        for t in [0.25, 0.5, 0.75] {
+            let offset = sticker_center_at(t) - sticker_center_at(0.0);
+            assert!((offset - full_offset * t).magnitude() < 0.0001);
+        }
+
+        // Exploded stickers must stay within the projection radius.
+        let params = StickerGeometryParams::new(&exploded, p.ty(), None, Quaternion::one());
+        let sticker_center = p.sticker_center_3d(Sticker(0), params);
+        assert!(sticker_center.to_vec().magnitude() <= p.projection_radius_3d(params));
+    }
+
+    #[test]
+    fn test_rubiks_3d_random_state() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let mut rng = scramble_rng(0);
+        let total_twist = |p: &Rubiks3D| {
+            (0..p.pieces().len() as _)
+                .map(|i| p.corner_twist(Piece(i)))
+                .sum::<u8>()
+                % 3
+        };
+
+        // Twisting never changes the total twist of the corners.
+        let mut p = Rubiks3D::new(2);
+        for _ in 0..100 {
+            p.twist(Twist::from_rng(ty, &mut rng)).unwrap();
+            assert_eq!(total_twist(&p), 0);
+        }
+
+        for _ in 0..100 {
+            let p = Rubiks3D::random_state(2, &mut rng).unwrap();
+            assert_eq!(total_twist(&p), 0);
+            let locations = (0..p.pieces().len() as _)
+                .map(|i| p.piece_location(Piece(i)))
+                .unique()
+                .count();
+            assert_eq!(locations, p.pieces().len());
+        }
+
+        assert!(Rubiks3D::random_state(3, &mut rng).is_none());
+    }
+
     fn twist_comparison_key(p: &Rubiks3D, twist: Twist) -> impl PartialEq {
         const SOME_PROGRESS: f32 = 0.1;
 
@@ -1,10 +1,11 @@
 //! 4D Rubik's cube.
 
+use bitvec::vec::BitVec;
 use cgmath::*;
 use itertools::Itertools;
 use num_enum::FromPrimitive;
 use serde::{de::Error, Deserialize, Deserializer};
-use smallvec::smallvec;
+use smallvec::{smallvec, SmallVec};
 use std::collections::HashMap;
 use std::ops::{Index, IndexMut, RangeInclusive};
 use std::sync::Mutex;
@@ -244,13 +245,14 @@ impl PuzzleType for Rubiks4DDescription {
     fn projection_radius_3d(&self, p: StickerGeometryParams) -> f32 {
         let r = 1.0 - p.face_spacing;
         let farthest_point = cgmath::vec4(1.0, r, r, r);
-        match p.project_4d(farthest_point) {
+        let radius = match p.project_4d(farthest_point) {
             Some(farthest_point) => p
                 .view_transform
                 .transform_point(farthest_point)
                 .distance(Point3::origin()),
             None => 3.0_f32.sqrt(), // shouldn't ever happen
-        }
+        };
+        radius * (1.0 + p.piece_explode)
     }
     fn scramble_moves_count(&self) -> usize {
         15 * self.layer_count as usize // TODO pulled from thin air; probably insufficient for big cubes
@@ -541,6 +543,12 @@ impl PuzzleState for Rubiks4D {
         }
         true
     }
+
+    fn sticker_location(&self, sticker: Sticker) -> (Face, SmallVec<[u8; 4]>) {
+        let piece = self.info(sticker).piece;
+        let location = self.piece_location(piece).into_iter().collect();
+        (self.sticker_face(sticker).into(), location)
+    }
 }
 #[delegate_to_methods]
 #[delegate(PuzzleType, target_ref = "desc")]
@@ -555,6 +563,14 @@ impl Rubiks4D {
         self.desc
     }
 
+    /// Returns the set of pieces whose position or orientation differs
+    /// between two states of the same puzzle.
+    pub fn diff(&self, other: &Self) -> BitVec {
+        std::iter::zip(&*self.piece_states, &*other.piece_states)
+            .map(|(a, b)| a != b)
+            .collect()
+    }
+
     fn piece_location(&self, piece: Piece) -> [u8; 4] {
         let piece_state = self[piece];
         let initial_location = self.desc.piece_locations[piece.0 as usize];
@@ -630,11 +646,14 @@ impl Rubiks4D {
     fn sticker_center_4d(&self, sticker: Sticker, p: StickerGeometryParams) -> Vector4<f32> {
         let sticker_info = self.info(sticker);
         let piece = sticker_info.piece;
-        let mut ret = self.piece_center_4d(piece, p);
+        let piece_center = self.piece_center_4d(piece, p);
+        let mut ret = piece_center;
 
         let sticker_face = self.sticker_face(sticker);
         ret[sticker_face.axis() as usize] = sticker_face.sign().float();
-        ret
+
+        // Move the whole piece away from the center.
+        ret + piece_center * p.piece_explode
     }
 
     fn piece_center_coordinate(&self, x: u8, p: StickerGeometryParams) -> f32 {
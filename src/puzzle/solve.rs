@@ -0,0 +1,686 @@
+//! Brute-force solver for small puzzles.
+
+use instant::{Duration, Instant};
+use itertools::Itertools;
+use rand::Rng;
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::*;
+
+/// Number of puzzle states to visit between calls to the progress callback.
+const PROGRESS_INTERVAL: usize = 10_000;
+
+/// Limits on how much work the solver may do before giving up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SolveBudget {
+    /// Maximum number of puzzle states to visit.
+    pub max_nodes: usize,
+    /// Maximum wall-clock time to spend searching.
+    pub max_time: Option<Duration>,
+}
+impl Default for SolveBudget {
+    fn default() -> Self {
+        Self {
+            max_nodes: 1_000_000,
+            max_time: Some(Duration::from_secs(5)),
+        }
+    }
+}
+
+/// Searches for a sequence of twists that solves the puzzle using iterative
+/// deepening depth-first search. Only outer-layer twists are considered, so
+/// this is only practical for puzzles with two layers (2x2x2, 2x2x2x2).
+///
+/// The solution starts with whole-puzzle rotations that bring the first piece
+/// back to where it started, and every twist after that leaves that piece
+/// alone. On a puzzle with two layers, this doesn't make any solution longer.
+///
+/// If a pruning table is given, it is used as a heuristic to skip branches
+/// that cannot lead to a solution at the current depth.
+///
+/// Returns `None` if no solution was found within the budget.
+pub fn solve(
+    puzzle: &Puzzle,
+    budget: SolveBudget,
+    pruning_table: Option<&PruningTable>,
+) -> Option<Vec<Twist>> {
+    solve_with_progress(
+        puzzle,
+        budget,
+        pruning_table,
+        &AtomicBool::new(false),
+        |_, _| (),
+    )
+}
+/// Same as `solve()`, but calls `progress` every so often with the number of
+/// states visited so far and the maximum number of states in the budget. If
+/// `cancel` becomes `true`, the search stops and `None` is returned.
+pub fn solve_with_progress(
+    puzzle: &Puzzle,
+    budget: SolveBudget,
+    pruning_table: Option<&PruningTable>,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, usize),
+) -> Option<Vec<Twist>> {
+    let (rotations, solution) =
+        solve_fixed_piece(puzzle, budget, pruning_table, cancel, &mut progress)?;
+    Some(rotations.into_iter().chain(solution).collect())
+}
+
+/// Returns a sequence of twists that takes a solved puzzle to `target` (up to
+/// whole-puzzle rotation), found by solving `target` and reversing the
+/// solution.
+///
+/// Returns `None` if no solution was found within the budget.
+pub fn scramble_to_state(
+    target: &Puzzle,
+    budget: SolveBudget,
+    pruning_table: Option<&PruningTable>,
+) -> Option<Vec<Twist>> {
+    scramble_to_state_with_progress(
+        target,
+        budget,
+        pruning_table,
+        &AtomicBool::new(false),
+        |_, _| (),
+    )
+}
+/// Same as `scramble_to_state()`, but with progress and cancellation like
+/// `solve_with_progress()`.
+pub fn scramble_to_state_with_progress(
+    target: &Puzzle,
+    budget: SolveBudget,
+    pruning_table: Option<&PruningTable>,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, usize),
+) -> Option<Vec<Twist>> {
+    // The rotations only change the orientation of the whole puzzle, so they
+    // can be left out of the scramble.
+    let (_rotations, solution) =
+        solve_fixed_piece(target, budget, pruning_table, cancel, &mut progress)?;
+    Some(
+        solution
+            .into_iter()
+            .rev()
+            .map(|twist| target.reverse_twist(twist))
+            .collect(),
+    )
+}
+
+/// Returns a uniformly random state of the puzzle along with a scramble that
+/// takes a solved puzzle to that state (up to whole-puzzle rotation). This may
+/// take a while, so `progress` and `cancel` work like in
+/// `solve_with_progress()`.
+///
+/// Returns an error if the puzzle doesn't support random states, if no
+/// scramble was found within the budget, or if the search was cancelled.
+pub fn random_state_scramble(
+    ty: PuzzleTypeEnum,
+    rng: &mut impl Rng,
+    budget: SolveBudget,
+    pruning_table: Option<&PruningTable>,
+    cancel: &AtomicBool,
+    progress: impl FnMut(usize, usize),
+) -> Result<(Puzzle, Vec<Twist>), &'static str> {
+    let target = Puzzle::random_state(ty, rng)
+        .ok_or("Random-state scrambles aren't supported for this puzzle")?;
+    match scramble_to_state_with_progress(&target, budget, pruning_table, cancel, progress) {
+        Some(scramble) => Ok((target, scramble)),
+        None if cancel.load(Ordering::Relaxed) => Err("Scramble cancelled"),
+        None => Err("Couldn't find a scramble for the random state"),
+    }
+}
+
+/// Rotates the puzzle so that the fixed piece is where it started, and then
+/// searches for a solution that doesn't move the fixed piece. Returns the
+/// rotations and the solution separately.
+fn solve_fixed_piece(
+    puzzle: &Puzzle,
+    budget: SolveBudget,
+    pruning_table: Option<&PruningTable>,
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Option<(Vec<Twist>, Vec<Twist>)> {
+    let solved = Puzzle::new(puzzle.ty());
+    let home = fixed_piece_location(&solved);
+    let (rotations, rotated) = rotate_fixed_piece_home(puzzle, &home)?;
+
+    let mut solver = Solver {
+        twists: fixed_piece_twists(&solved),
+        puzzle: rotated,
+        budget,
+        pruning_table,
+        cancel,
+        progress,
+        start_time: Instant::now(),
+        nodes: 0,
+        solution: vec![],
+    };
+
+    for depth in 0.. {
+        match solver.search(depth) {
+            SearchResult::Found => return Some((rotations, solver.solution)),
+            SearchResult::NotFound => (),
+            SearchResult::OutOfBudget => return None,
+        }
+    }
+    None
+}
+
+/// Piece that the solver never moves. On a puzzle with two layers, every twist
+/// that moves this piece is equivalent to a twist that doesn't, followed by a
+/// whole-puzzle rotation.
+const FIXED_PIECE: Piece = Piece(0);
+
+/// Returns the face and location of each sticker of the fixed piece.
+fn fixed_piece_location(puzzle: &Puzzle) -> Vec<(Face, SmallVec<[u8; 4]>)> {
+    puzzle
+        .info(FIXED_PIECE)
+        .stickers
+        .iter()
+        .map(|&sticker| puzzle.sticker_location(sticker))
+        .collect()
+}
+
+/// Returns whole-puzzle rotations that take the fixed piece back to `home`,
+/// along with the rotated puzzle.
+fn rotate_fixed_piece_home(
+    puzzle: &Puzzle,
+    home: &[(Face, SmallVec<[u8; 4]>)],
+) -> Option<(Vec<Twist>, Puzzle)> {
+    let rotations = itertools::iproduct!(
+        (0..puzzle.twist_axes().len() as _).map(TwistAxis),
+        (0..puzzle.twist_directions().len() as _).map(TwistDirection)
+    )
+    .map(|(axis, direction)| Twist {
+        axis,
+        direction,
+        layers: puzzle.all_layers(),
+    })
+    .collect_vec();
+
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(puzzle.color_layout());
+    queue.push_back((vec![], puzzle.clone()));
+    while let Some((path, state)) = queue.pop_front() {
+        if fixed_piece_location(&state) == home {
+            return Some((path, state));
+        }
+        for &twist in &rotations {
+            let mut new_state = state.clone();
+            if new_state.twist(twist).is_err() {
+                continue;
+            }
+            if seen.insert(new_state.color_layout()) {
+                let mut new_path = path.clone();
+                new_path.push(twist);
+                queue.push_back((new_path, new_state));
+            }
+        }
+    }
+    None
+}
+
+/// Returns every outer-layer twist of the puzzle that doesn't move the fixed
+/// piece, leaving out twists that do the same thing as another one (such as
+/// clockwise and counterclockwise 180-degree twists).
+fn fixed_piece_twists(solved: &Puzzle) -> Vec<Twist> {
+    let home = fixed_piece_location(solved);
+    outer_layer_twists(solved)
+        .into_iter()
+        .filter_map(|twist| {
+            let mut state = solved.clone();
+            state.twist(twist).ok()?;
+            (fixed_piece_location(&state) == home).then(|| (twist, state.color_layout()))
+        })
+        .unique_by(|(_twist, layout)| layout.clone())
+        .map(|(twist, _layout)| twist)
+        .collect()
+}
+
+/// Returns every distinct outer-layer twist of the puzzle.
+fn outer_layer_twists(puzzle: &Puzzle) -> Vec<Twist> {
+    itertools::iproduct!(
+        (0..puzzle.twist_axes().len() as _).map(TwistAxis),
+        (0..puzzle.twist_directions().len() as _).map(TwistDirection)
+    )
+    .map(|(axis, direction)| {
+        puzzle.canonicalize_twist(Twist {
+            axis,
+            direction,
+            layers: LayerMask(1),
+        })
+    })
+    .unique()
+    .collect()
+}
+
+enum SearchResult {
+    Found,
+    NotFound,
+    OutOfBudget,
+}
+
+struct Solver<'a> {
+    twists: Vec<Twist>,
+    puzzle: Puzzle,
+    budget: SolveBudget,
+    pruning_table: Option<&'a PruningTable>,
+    cancel: &'a AtomicBool,
+    progress: &'a mut dyn FnMut(usize, usize),
+    start_time: Instant,
+    nodes: usize,
+    /// Twists applied so far along the current search path.
+    solution: Vec<Twist>,
+}
+impl Solver<'_> {
+    fn is_out_of_budget(&self) -> bool {
+        self.nodes >= self.budget.max_nodes
+            || self.cancel.load(Ordering::Relaxed)
+            || self
+                .budget
+                .max_time
+                .map_or(false, |max_time| self.start_time.elapsed() >= max_time)
+    }
+
+    fn search(&mut self, remaining_depth: usize) -> SearchResult {
+        self.nodes += 1;
+        if self.nodes % PROGRESS_INTERVAL == 0 {
+            (self.progress)(self.nodes, self.budget.max_nodes);
+        }
+        if self.puzzle.is_solved() {
+            return SearchResult::Found;
+        }
+        if remaining_depth == 0 {
+            return SearchResult::NotFound;
+        }
+        if let Some(table) = self.pruning_table {
+            if table.lower_bound(&self.puzzle) > remaining_depth {
+                return SearchResult::NotFound;
+            }
+        }
+        if self.is_out_of_budget() {
+            return SearchResult::OutOfBudget;
+        }
+
+        for i in 0..self.twists.len() {
+            let twist = self.twists[i];
+            // Consecutive twists on the same axis could always be combined.
+            if self.solution.last().map(|prev| prev.axis) == Some(twist.axis) {
+                continue;
+            }
+
+            if self.puzzle.twist(twist).is_err() {
+                continue;
+            }
+            self.solution.push(twist);
+            match self.search(remaining_depth - 1) {
+                SearchResult::Found => return SearchResult::Found,
+                SearchResult::NotFound => (),
+                SearchResult::OutOfBudget => return SearchResult::OutOfBudget,
+            }
+            self.solution.pop();
+            let _ = self.puzzle.twist(self.puzzle.reverse_twist(twist));
+        }
+
+        SearchResult::NotFound
+    }
+}
+
+/// Table of the exact number of outer-layer twists needed to solve every
+/// state within a fixed distance of solved, using only twists that don't move
+/// the fixed piece. States that are not in the table are known to be farther
+/// away, so the table gives an admissible heuristic for `solve()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PruningTable {
+    puzzle: PuzzleTypeEnum,
+    depth: usize,
+    /// Location of the fixed piece in the solved state. The table only
+    /// applies to states where the fixed piece is in the same location.
+    fixed_piece_home: Vec<(Face, SmallVec<[u8; 4]>)>,
+    /// Distance to solved for each state, keyed by color layout.
+    distances: HashMap<Vec<u16>, u8>,
+}
+impl PruningTable {
+    /// Magic bytes at the start of a pruning table file.
+    const FILE_MAGIC: &'static [u8] = b"HSC pruning table\n";
+    /// Version of the pruning table file format.
+    const FILE_VERSION: u8 = 1;
+
+    /// Generates a pruning table for every state up to `depth` twists from
+    /// solved using breadth-first search.
+    pub fn generate(ty: PuzzleTypeEnum, depth: usize) -> Self {
+        let solved = Puzzle::new(ty);
+        let twists = fixed_piece_twists(&solved);
+
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(solved.color_layout(), 0);
+        queue.push_back((solved.clone(), 0));
+        while let Some((state, distance)) = queue.pop_front() {
+            if distance >= depth {
+                continue;
+            }
+            for &twist in &twists {
+                let mut new_state = state.clone();
+                if new_state.twist(twist).is_err() {
+                    continue;
+                }
+                let key = new_state.color_layout();
+                if !distances.contains_key(&key) {
+                    distances.insert(key, distance as u8 + 1);
+                    queue.push_back((new_state, distance + 1));
+                }
+            }
+        }
+
+        Self::with_distances(ty, depth, distances)
+    }
+    fn with_distances(ty: PuzzleTypeEnum, depth: usize, distances: HashMap<Vec<u16>, u8>) -> Self {
+        Self {
+            puzzle: ty,
+            depth,
+            fixed_piece_home: fixed_piece_location(&Puzzle::new(ty)),
+            distances,
+        }
+    }
+
+    /// Returns a pruning table that has already been loaded or generated, or
+    /// loads or generates one if this is the first time it's needed.
+    pub fn get(ty: PuzzleTypeEnum, depth: usize) -> Arc<Self> {
+        lazy_static! {
+            static ref CACHE: Mutex<HashMap<(PuzzleTypeEnum, usize), Arc<PruningTable>>> =
+                Mutex::new(HashMap::new());
+        }
+
+        Arc::clone(CACHE.lock().unwrap().entry((ty, depth)).or_insert_with(|| {
+            #[cfg(target_arch = "wasm32")]
+            let table = Self::generate(ty, depth);
+            #[cfg(not(target_arch = "wasm32"))]
+            let table = Self::load_or_generate(ty, depth);
+            Arc::new(table)
+        }))
+    }
+
+    /// Loads a pruning table from the cache directory, or generates one and
+    /// saves it there if it isn't cached yet.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_or_generate(ty: PuzzleTypeEnum, depth: usize) -> Self {
+        let cache_dir = match crate::preferences::cache_dir() {
+            Ok(dir) => Some(dir.join("pruning_tables")),
+            Err(e) => {
+                log::warn!("Unable to cache pruning table: {e}");
+                None
+            }
+        };
+        Self::load_or_generate_in(cache_dir.as_deref(), ty, depth)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_or_generate_in(
+        cache_dir: Option<&std::path::Path>,
+        ty: PuzzleTypeEnum,
+        depth: usize,
+    ) -> Self {
+        let path = cache_dir.map(|dir| dir.join(format!("{}_{depth}.bin", ty.name())));
+
+        if let Some(path) = &path {
+            match std::fs::read(path) {
+                Ok(bytes) => match Self::from_bytes(ty, depth, &bytes) {
+                    Ok(table) => return table,
+                    Err(e) => log::warn!("Error loading pruning table from {path:?}: {e}"),
+                },
+                Err(e) => log::debug!("No cached pruning table at {path:?}: {e}"),
+            }
+        }
+
+        let table = Self::generate(ty, depth);
+        if let Some(path) = &path {
+            if let Err(e) = table.save(path) {
+                log::warn!("Error saving pruning table to {path:?}: {e}");
+            }
+        }
+        table
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Encodes the table in a compact binary format: a header followed by
+    /// each color layout and its distance, with every number little-endian.
+    fn to_bytes(&self) -> Vec<u8> {
+        let key_len = self.distances.keys().next().map_or(0, |key| key.len());
+        let name = self.puzzle.name();
+
+        let mut ret = Self::FILE_MAGIC.to_vec();
+        ret.push(Self::FILE_VERSION);
+        ret.push(name.len() as u8);
+        ret.extend(name.as_bytes());
+        ret.push(self.depth as u8);
+        ret.extend((key_len as u32).to_le_bytes());
+        ret.extend((self.distances.len() as u32).to_le_bytes());
+        for (key, &distance) in &self.distances {
+            ret.extend(key.iter().flat_map(|x| x.to_le_bytes()));
+            ret.push(distance);
+        }
+        ret
+    }
+    /// Decodes a table encoded using `to_bytes()`, checking that it is for the
+    /// given puzzle and depth.
+    fn from_bytes(ty: PuzzleTypeEnum, depth: usize, mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let u32_le = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap()) as usize;
+
+        let magic = take_bytes(&mut bytes, Self::FILE_MAGIC.len())?;
+        anyhow::ensure!(magic == Self::FILE_MAGIC, "not a pruning table");
+        let version = take_bytes(&mut bytes, 1)?[0];
+        anyhow::ensure!(
+            version == Self::FILE_VERSION,
+            "unsupported version {version}"
+        );
+        let name_len = take_bytes(&mut bytes, 1)?[0] as usize;
+        let name = take_bytes(&mut bytes, name_len)?;
+        anyhow::ensure!(
+            name == ty.name().as_bytes(),
+            "table is for a different puzzle"
+        );
+        let file_depth = take_bytes(&mut bytes, 1)?[0] as usize;
+        anyhow::ensure!(file_depth == depth, "table is for a different depth");
+        let key_len = u32_le(take_bytes(&mut bytes, 4)?);
+        let count = u32_le(take_bytes(&mut bytes, 4)?);
+
+        let mut distances = HashMap::new();
+        for _ in 0..count {
+            let key = take_bytes(&mut bytes, key_len * 2)?
+                .chunks_exact(2)
+                .map(|x| u16::from_le_bytes([x[0], x[1]]))
+                .collect();
+            distances.insert(key, take_bytes(&mut bytes, 1)?[0]);
+        }
+        anyhow::ensure!(bytes.is_empty(), "unexpected data at end of file");
+
+        Ok(Self::with_distances(ty, depth, distances))
+    }
+
+    /// Returns a lower bound on the number of twists needed to solve the
+    /// puzzle without moving the fixed piece.
+    pub fn lower_bound(&self, puzzle: &Puzzle) -> usize {
+        if puzzle.ty() != self.puzzle || fixed_piece_location(puzzle) != self.fixed_piece_home {
+            return 0;
+        }
+        match self.distances.get(&puzzle.color_layout()) {
+            Some(&distance) => distance as usize,
+            None => self.depth + 1,
+        }
+    }
+}
+
+/// Removes the first `n` bytes from `bytes` and returns them.
+fn take_bytes<'a>(bytes: &mut &'a [u8], n: usize) -> anyhow::Result<&'a [u8]> {
+    anyhow::ensure!(n <= bytes.len(), "unexpected end of file");
+    let (ret, rest) = bytes.split_at(n);
+    *bytes = rest;
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_2x2x2x2() {
+        let ty = PuzzleTypeEnum::Rubiks4D { layer_count: 2 };
+        let twist = |axis| Twist {
+            axis: TwistAxis(axis),
+            direction: TwistDirection(0),
+            layers: LayerMask(1),
+        };
+
+        let mut puzzle = Puzzle::new(ty);
+        for axis in [0, 2] {
+            puzzle.twist(twist(axis)).unwrap();
+        }
+        assert!(!puzzle.is_solved());
+
+        let solution = solve(&puzzle, SolveBudget::default(), None).expect("no solution found");
+        let is_rotation = |twist: &Twist| twist.layers == puzzle.all_layers();
+        assert!(solution.iter().filter(|t| !is_rotation(t)).count() <= 2);
+        for twist in solution {
+            puzzle.twist(twist).unwrap();
+        }
+        assert!(puzzle.is_solved());
+
+        // The solver gives up when out of budget.
+        let mut puzzle = Puzzle::new(ty);
+        for axis in [0, 2, 4] {
+            puzzle.twist(twist(axis)).unwrap();
+        }
+        let tiny_budget = SolveBudget {
+            max_nodes: 10,
+            max_time: None,
+        };
+        assert_eq!(solve(&puzzle, tiny_budget, None), None);
+    }
+
+    #[test]
+    fn test_scramble_to_state() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let twists = outer_layer_twists(&Puzzle::new(ty));
+
+        let mut target = Puzzle::new(ty);
+        for &twist in &twists[..3] {
+            target.twist(twist).unwrap();
+        }
+
+        let scramble = scramble_to_state(&target, SolveBudget::default(), None).unwrap();
+        let mut puzzle = Puzzle::new(ty);
+        for twist in scramble {
+            puzzle.twist(twist).unwrap();
+        }
+        assert_eq!(puzzle.color_signature(), target.color_signature());
+    }
+
+    #[test]
+    fn test_random_state_scramble() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let table = PruningTable::generate(ty, 4);
+        let budget = SolveBudget {
+            max_nodes: 10_000_000,
+            max_time: None,
+        };
+        let no_cancel = AtomicBool::new(false);
+
+        let mut rng = scramble_rng(0);
+        for _ in 0..3 {
+            let (target, scramble) =
+                random_state_scramble(ty, &mut rng, budget, Some(&table), &no_cancel, |_, _| ())
+                    .unwrap();
+            let mut puzzle = Puzzle::new(ty);
+            for twist in scramble {
+                puzzle.twist(twist).unwrap();
+            }
+            // The scramble reaches the target up to whole-puzzle rotation.
+            assert!(puzzle
+                .orientations()
+                .iter()
+                .any(|state| state.color_layout() == target.color_layout()));
+        }
+
+        // Cancelling stops the search.
+        let cancel = AtomicBool::new(true);
+        assert_eq!(
+            random_state_scramble(ty, &mut rng, budget, None, &cancel, |_, _| ()),
+            Err("Scramble cancelled"),
+        );
+
+        for unsupported in [
+            PuzzleTypeEnum::Rubiks3D { layer_count: 3 },
+            PuzzleTypeEnum::Rubiks4D { layer_count: 2 },
+        ] {
+            assert!(random_state_scramble(
+                unsupported,
+                &mut rng,
+                SolveBudget::default(),
+                None,
+                &no_cancel,
+                |_, _| (),
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_pruning_table_is_admissible() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let table = PruningTable::generate(ty, 2);
+        assert_eq!(table.lower_bound(&Puzzle::new(ty)), 0);
+
+        // The table only applies when the fixed piece hasn't moved.
+        let twists = fixed_piece_twists(&Puzzle::new(ty));
+        for scramble in twists.iter().combinations(3).step_by(7) {
+            let mut puzzle = Puzzle::new(ty);
+            for &&twist in &scramble {
+                puzzle.twist(twist).unwrap();
+            }
+
+            // Brute-force search always finds an optimal solution.
+            let optimal = solve(&puzzle, SolveBudget::default(), None).unwrap();
+            assert!(table.lower_bound(&puzzle) <= optimal.len());
+
+            // Using the table as a heuristic still finds an optimal solution.
+            let with_table = solve(&puzzle, SolveBudget::default(), Some(&table)).unwrap();
+            assert_eq!(with_table.len(), optimal.len());
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn test_pruning_table_cache() {
+        let ty = PuzzleTypeEnum::Rubiks3D { layer_count: 2 };
+        let cache_dir = std::env::temp_dir().join(format!(
+            "hyperspeedcube_test_pruning_tables_{}",
+            std::process::id(),
+        ));
+        let path = cache_dir.join(format!("{}_2.bin", ty.name()));
+
+        let generated = PruningTable::load_or_generate_in(Some(&cache_dir), ty, 2);
+        assert!(path.exists());
+        let loaded = PruningTable::load_or_generate_in(Some(&cache_dir), ty, 2);
+        assert_eq!(loaded, generated);
+
+        // A table for a different depth is not loaded from the wrong file.
+        let deeper = PruningTable::load_or_generate_in(Some(&cache_dir), ty, 3);
+        assert_eq!(deeper.depth, 3);
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
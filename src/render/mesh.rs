@@ -11,10 +11,37 @@ use crate::util::IterCyclicPairsExt;
 const OUTLINE_SCALE: f32 = 1.0 / 512.0;
 const OUTLINE_WEDGE_VERTS_PER_RADIAN: f32 = 3.0;
 
+/// Region of puzzle space that the vertex shader maps onto the viewport.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) struct ViewportBounds {
+    min: Point2<f32>,
+    max: Point2<f32>,
+}
+impl ViewportBounds {
+    /// Returns the visible region given the same scale and alignment that are
+    /// passed to the vertex shader.
+    pub(super) fn new(scale: Vector2<f32>, align: Vector2<f32>) -> Self {
+        Self {
+            min: cgmath::point2((-1.0 - align.x) / scale.x, (-1.0 - align.y) / scale.y),
+            max: cgmath::point2((1.0 - align.x) / scale.x, (1.0 - align.y) / scale.y),
+        }
+    }
+
+    /// Returns whether any part of a sticker, including its outline, may be
+    /// visible. This is conservative: it only checks the bounding box.
+    fn may_contain(&self, geom: &ProjectedStickerGeometry, outline_radius: f32) -> bool {
+        geom.min_bound.x - outline_radius <= self.max.x
+            && geom.min_bound.y - outline_radius <= self.max.y
+            && self.min.x <= geom.max_bound.x + outline_radius
+            && self.min.y <= geom.max_bound.y + outline_radius
+    }
+}
+
 pub(super) fn make_puzzle_mesh(
     puzzle: &mut PuzzleController,
     prefs: &Preferences,
     sticker_geometries: &[ProjectedStickerGeometry],
+    viewport: ViewportBounds,
 ) -> (Vec<RgbaVertex>, Vec<u32>) {
     // Triangulate polygons and combine the whole puzzle into one mesh.
     let mut verts = vec![];
@@ -33,6 +60,13 @@ pub(super) fn make_puzzle_mesh(
 
         let visual_state = puzzle.visual_piece_state(sticker_info.piece);
 
+        // Skip stickers that are entirely off-screen. This happens after
+        // projection, so 4D and 3D perspective are already accounted for.
+        let outline_size = visual_state.outline_size(prefs);
+        if !viewport.may_contain(geom, outline_size * OUTLINE_SCALE) {
+            continue;
+        }
+
         // Determine sticker alpha.
         let alpha = visual_state.opacity(prefs);
 
@@ -48,7 +82,6 @@ pub(super) fn make_puzzle_mesh(
         let outline_color = visual_state
             .outline_color(prefs, puzzle.selection().contains(&geom.sticker))
             .multiply(alpha);
-        let outline_size = visual_state.outline_size(prefs);
 
         // Generate outline vertices.
         if outline_size > 0.0 {
@@ -188,3 +221,41 @@ fn generate_outline_geometry(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_sticker(min: Point2<f32>, max: Point2<f32>) -> ProjectedStickerGeometry {
+        ProjectedStickerGeometry {
+            sticker: Sticker(0),
+
+            verts: Box::new([]),
+            min_bound: cgmath::point3(min.x, min.y, 0.0),
+            max_bound: cgmath::point3(max.x, max.y, 0.0),
+
+            front_polygons: Box::new([]),
+            back_polygons: Box::new([]),
+        }
+    }
+
+    #[test]
+    fn test_viewport_culling() {
+        // Zoomed in 4x and shifted so that only part of the puzzle is visible.
+        let viewport = ViewportBounds::new(cgmath::vec2(4.0, 4.0), cgmath::vec2(1.0, 0.0));
+        assert_eq!(viewport.min, cgmath::point2(-0.5, -0.25));
+        assert_eq!(viewport.max, cgmath::point2(0.0, 0.25));
+
+        let on_screen = square_sticker(cgmath::point2(-0.3, -0.1), cgmath::point2(-0.2, 0.1));
+        assert!(viewport.may_contain(&on_screen, 0.0));
+
+        let partially_on_screen =
+            square_sticker(cgmath::point2(-0.1, 0.2), cgmath::point2(0.1, 0.4));
+        assert!(viewport.may_contain(&partially_on_screen, 0.0));
+
+        let off_screen = square_sticker(cgmath::point2(0.1, -0.1), cgmath::point2(0.2, 0.1));
+        assert!(!viewport.may_contain(&off_screen, 0.0));
+        // The outline may still poke into the viewport.
+        assert!(viewport.may_contain(&off_screen, 0.15));
+    }
+}
@@ -11,6 +11,7 @@ mod structs;
 
 use crate::app::App;
 use crate::puzzle::ProjectedStickerGeometry;
+use crate::util;
 use cache::{CachedDynamicBuffer, CachedUniformBuffer};
 pub(crate) use state::GraphicsState;
 use structs::*;
@@ -102,7 +103,12 @@ pub(crate) fn draw_puzzle(
     gfx: &mut GraphicsState,
     mut force_redraw: bool,
 ) -> Option<wgpu::TextureView> {
-    let (width, height) = app.puzzle_texture_size;
+    // Render at a different resolution than the on-screen size; egui will
+    // resample the texture when it draws it.
+    let (width, height) = app.prefs.gfx.render_target_size(
+        app.puzzle_texture_size,
+        gfx.device.limits().max_texture_dimension_2d,
+    );
     let size = cgmath::vec2(width as f32, height as f32);
 
     // Avoid divide-by-zero errors.
@@ -167,7 +173,14 @@ pub(crate) fn draw_puzzle(
         let hovered_stickers = puzzle_geometry.iter().rev().filter_map(move |geom| {
             Some((geom.sticker, geom.twists_for_point(transformed_cursor_pos)?))
         });
+        let old_hovered_sticker = puzzle.hovered_sticker();
         puzzle.update_hovered_sticker(hovered_stickers);
+        if let Some(sticker) = puzzle.hovered_sticker() {
+            if old_hovered_sticker != Some(sticker) {
+                let pos: [f32; 2] = transformed_cursor_pos.into();
+                log::trace!("Hovering {sticker:?} at {}", util::format_vector(&pos, 3));
+            }
+        }
     } else {
         puzzle.update_hovered_sticker([]);
     }
@@ -182,7 +195,10 @@ pub(crate) fn draw_puzzle(
     }
 
     // Generate the mesh.
-    let (mut verts, mut indices) = mesh::make_puzzle_mesh(puzzle, prefs, &puzzle_geometry);
+    let viewport =
+        mesh::ViewportBounds::new(scale, cgmath::vec2(view_prefs.align_h, view_prefs.align_v));
+    let (mut verts, mut indices) =
+        mesh::make_puzzle_mesh(puzzle, prefs, &puzzle_geometry, viewport);
 
     // Create "out" texture that will ultimately be returned.
     let (out_texture, out_texture_view) = cache.out_texture.get_or_insert_with(|| {
@@ -1,4 +1,5 @@
 use cgmath::Point3;
+use std::fmt;
 use std::ops::{Add, Mul};
 
 pub const INVALID_STR: &str = "<invalid>";
@@ -106,3 +107,49 @@ where
 {
     a * (1.0 - t) + b * t
 }
+
+/// Formats a vector with a fixed number of decimal places, such as
+/// `[1.000, 0.333]`.
+pub fn format_vector(v: &[f32], decimals: usize) -> String {
+    VectorDisplay { v, decimals }.to_string()
+}
+
+/// Wrapper that displays a vector with a fixed number of decimal places, for
+/// use in log messages.
+#[derive(Debug, Copy, Clone)]
+pub struct VectorDisplay<'a> {
+    pub v: &'a [f32],
+    pub decimals: usize,
+}
+impl fmt::Display for VectorDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, x) in self.v.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{x:.*}", self.decimals)?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_vector() {
+        assert_eq!(format_vector(&[1.0, 0.3333333], 3), "[1.000, 0.333]");
+        assert_eq!(format_vector(&[-2.4], 0), "[-2]");
+        assert_eq!(format_vector(&[], 3), "[]");
+
+        let p = cgmath::point3(0.5, -0.25, 1.0 / 3.0);
+        let v: [f32; 3] = p.into();
+        let display = VectorDisplay { v: &v, decimals: 2 };
+        assert_eq!(
+            format!("Point at {display}"),
+            "Point at [0.50, -0.25, 0.33]"
+        );
+    }
+}